@@ -1,5 +1,10 @@
+use std::time::Duration;
+
 use structopt::StructOpt;
 
+use crate::date_time::parse_duration_human_readable;
+use crate::session::schedule::Repeat;
+
 #[derive(StructOpt, Debug)]
 pub enum Command {
     InitSessionDir,
@@ -7,21 +12,75 @@ pub enum Command {
     Watch,
     Tui,
     Start {
+        /// Minutes [default: 25 or the preset's duration]
+        #[structopt(short = "t", long = "duration")]
+        duration: Option<u64>,
+
+        /// [default: "no description" or the preset's description]
+        #[structopt(short = "d", long = "description")]
+        description: Option<String>,
+
+        /// Name of a preset from the config, options given here override it
+        #[structopt(short = "p", long = "preset")]
+        preset: Option<String>,
+
+        /// Keep counting past the duration until the session is finished
+        #[structopt(long = "overtime")]
+        overtime: bool,
+
+        /// Count up without a duration until the session is finished
+        #[structopt(long = "stopwatch", conflicts_with = "duration")]
+        stopwatch: bool,
+    },
+    /// Completes the running session now, counting it as done with the time it really ran
+    Stop,
+    /// Plans a session that `watch` or the TUI starts when it is due
+    Schedule {
+        description: String,
+        /// e.g. 09:45 (the next time it comes up) or "2025-06-02 09:45", in UTC like session times
+        #[structopt(long = "at")]
+        at: String,
+        /// daily, weekdays or weekly
+        #[structopt(long = "repeat")]
+        repeat: Option<Repeat>,
+        /// Minutes
         #[structopt(short = "t", long = "duration", default_value = "25")]
         duration: u64,
-
+    },
+    /// Records a done session afterwards, for work done without starting the timer
+    Log {
+        /// e.g. 14:00 (today) or "2025-05-31 14:00", in UTC like session times
+        #[structopt(long = "at")]
+        at: String,
+        /// e.g. 50m or 1h30m, a bare number counts as minutes
+        #[structopt(
+            short = "t",
+            long = "duration",
+            default_value = "25m",
+            parse(try_from_str = parse_duration_human_readable)
+        )]
+        duration: Duration,
         #[structopt(short = "d", long = "description", default_value = "no description")]
         description: String,
+        /// Comma separated, e.g. rust,review
+        #[structopt(long = "tags")]
+        tags: Option<String>,
     },
     FindSessionFromToday {
         #[structopt(short = "s", long = "search")]
         search_query: Option<String>,
+        /// Filter like the TUI search, e.g. "tag:rust -state:canceled duration>30m"
+        #[structopt(short = "q", long = "query")]
+        query: Option<String>,
         #[structopt(short = "e", long = "export")]
         export: bool,
     },
     FindSessionFromYesterday {
         #[structopt(short = "s", long = "search")]
         search_query: Option<String>,
+        /// Filter like the TUI search, e.g. "tag:rust -state:canceled duration>30m"
+        #[structopt(short = "q", long = "query")]
+        query: Option<String>,
         #[structopt(short = "e", long = "export")]
         export: bool,
     },
@@ -30,6 +89,32 @@ pub enum Command {
         end_date: String,
         #[structopt(short = "s", long = "search")]
         search_query: Option<String>,
+        /// Filter like the TUI search, e.g. "tag:rust -state:canceled duration>30m"
+        #[structopt(short = "q", long = "query")]
+        query: Option<String>,
+        #[structopt(short = "e", long = "export")]
+        export: bool,
+    },
+    /// Lists, restores or purges deleted sessions
+    Trash(TrashCommand),
+    /// Lists, renames, merges or deletes tags across all sessions
+    Tags(TagsCommand),
+    /// Upgrades all session files to the current format, keeping a backup of the originals
+    Migrate {
+        /// Only print a diff of the changes
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Finds sessions like the TUI does, by a saved view, a date filter and a query
+    Find {
+        /// Name of a view from the config
+        #[structopt(long = "view")]
+        view: Option<String>,
+        /// e.g. 2025-05-31, "2025-05-01 - 2025-05-31" or "this week"
+        #[structopt(short = "d", long = "date")]
+        date: Option<String>,
+        #[structopt(short = "q", long = "query")]
+        query: Option<String>,
         #[structopt(short = "e", long = "export")]
         export: bool,
     },
@@ -38,3 +123,43 @@ pub enum Command {
         number: u32,
     },
 }
+
+#[derive(StructOpt, Debug)]
+pub enum TrashCommand {
+    List,
+    Restore {
+        /// Id of the session, a unique prefix is enough
+        id: String,
+    },
+    /// Removes deleted session files from disk
+    Purge {
+        /// Only purge sessions deleted more than this many days ago
+        #[structopt(long = "older-than")]
+        older_than_days: Option<u32>,
+        /// Don't ask for confirmation
+        #[structopt(short = "y", long = "yes")]
+        yes: bool,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum TagsCommand {
+    /// Lists tags with the number of sessions using them
+    List,
+    /// Renames a tag in every session
+    Rename { old: String, new: String },
+    /// Replaces several tags with one, e.g. `tags merge Rust rust-lang --into rust`
+    Merge {
+        #[structopt(required = true)]
+        tags: Vec<String>,
+        #[structopt(long = "into")]
+        into: String,
+    },
+    /// Removes a tag from every session
+    Delete {
+        tag: String,
+        /// Don't ask for confirmation
+        #[structopt(short = "y", long = "yes")]
+        yes: bool,
+    },
+}
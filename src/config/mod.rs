@@ -1,11 +1,176 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::date_time::{deserialize_duration_human_readable, parse_duration_human_readable};
+use crate::session::{Session, SessionState};
 
 #[derive(Debug, Deserialize)]
 pub struct PomodoroConfig {
     pub pomodoro_session_dir: String,
+    /// Mouse support in the TUI, turn off to keep the terminal's own text selection
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+    /// Pomodoros before a long break
+    #[serde(default = "default_cycle_length")]
+    pub cycle_length: usize,
+    #[serde(
+        default = "default_short_break",
+        deserialize_with = "deserialize_duration_human_readable"
+    )]
+    pub short_break: Duration,
+    #[serde(
+        default = "default_long_break",
+        deserialize_with = "deserialize_duration_human_readable"
+    )]
+    pub long_break: Duration,
+    /// Pomodoros to aim for each day, shown in Zen mode
+    #[serde(default)]
+    pub daily_goal: Option<usize>,
+    /// Sessions keep counting past their duration until they are finished
+    #[serde(default)]
+    pub overtime: bool,
+}
+
+fn default_mouse() -> bool {
+    true
+}
+
+fn default_cycle_length() -> usize {
+    4
+}
+
+fn default_short_break() -> Duration {
+    Duration::from_secs(5 * 60)
+}
+
+fn default_long_break() -> Duration {
+    Duration::from_secs(15 * 60)
+}
+
+/// A named date and search filter combination.
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct View {
+    pub name: String,
+    #[serde(default)]
+    pub date: String,
+    #[serde(default)]
+    pub search: String,
+}
+
+/// A named kind of session that can be started without typing its details.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(
+        default = "default_preset_duration",
+        deserialize_with = "deserialize_duration_human_readable"
+    )]
+    pub duration: Duration,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub project: String,
+    /// Breaks after consecutive sessions of the preset, repeating, e.g. `["5m", "5m", "15m"]`
+    #[serde(default, deserialize_with = "deserialize_breaks")]
+    pub breaks: Vec<Duration>,
+}
+
+fn default_preset_duration() -> Duration {
+    Duration::from_secs(25 * 60)
+}
+
+fn deserialize_breaks<'de, D>(deserializer: D) -> Result<Vec<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|b| parse_duration_human_readable(b).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+impl Preset {
+    /// A running session with the preset's description, duration, tags and project.
+    pub fn to_session(&self, start: DateTime<Utc>) -> Session {
+        Session {
+            tags: self.tags.clone(),
+            project: self.project.clone(),
+            ..Session::new(self.description.clone(), self.duration, start)
+        }
+    }
+
+    /// The break due after the latest session of this preset started today, following
+    /// the break pattern from its start for every day.
+    pub fn next_break(&self, sessions: &[Session], today: chrono::NaiveDate) -> Option<Duration> {
+        let started_today = sessions
+            .iter()
+            .filter(|s| s.start.date_naive() == today && s.description == self.description)
+            .filter(|s| {
+                !matches!(
+                    s.state,
+                    SessionState::Planned | SessionState::Deleted | SessionState::Canceled
+                )
+            })
+            .count();
+        let index = started_today.checked_sub(1)? % self.breaks.len().max(1);
+        self.breaks.get(index).copied()
+    }
+}
+
+/// A built-in TUI theme name plus optional color overrides.
+#[derive(Debug, Deserialize, Default)]
+pub struct ThemeConfig {
+    pub name: Option<String>,
+    pub accent: Option<String>,
+    pub highlight: Option<String>,
+    pub error: Option<String>,
+    pub active_border: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub pomodoro_config: PomodoroConfig,
+    #[serde(default)]
+    pub views: Vec<View>,
+    /// TUI action names mapped to space separated keys, e.g. `down = "n down"`
+    #[serde(default)]
+    pub keymap: HashMap<String, String>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+}
+
+impl Config {
+    pub fn find_preset(&self, name: &str) -> Option<&Preset> {
+        self.presets
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn find_view(&self, name: &str) -> Option<&View> {
+        self.views
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Appends a view to the config file, leaving the rest of the file untouched.
+pub fn append_view(config_path: &Path, view: &View) -> io::Result<()> {
+    let quote = |s: &str| toml::Value::String(s.to_string()).to_string();
+    let mut file = OpenOptions::new().append(true).open(config_path)?;
+    write!(
+        file,
+        "\n[[views]]\nname = {}\ndate = {}\nsearch = {}\n",
+        quote(&view.name),
+        quote(&view.date),
+        quote(&view.search)
+    )
 }
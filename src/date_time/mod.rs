@@ -1,4 +1,7 @@
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc,
+};
 use serde::de::{self, Visitor};
 use serde::{Deserializer, Serializer};
 use std::fmt;
@@ -38,9 +41,304 @@ where
     deserializer.deserialize_str(DateTimeVisitor)
 }
 
+pub fn serialize_optional_human_readable<S>(
+    date: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match date {
+        Some(date) => serialize_human_readable(date, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+struct OptionalDateTimeVisitor;
+
+impl<'de> Visitor<'de> for OptionalDateTimeVisitor {
+    type Value = Option<DateTime<Utc>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("null or a date and time in the format %Y-%m-%d %H:%M:%S")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_human_readable(deserializer).map(Some)
+    }
+}
+
+pub fn deserialize_optional_human_readable<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_option(OptionalDateTimeVisitor)
+}
+
 pub fn duration_in_minutes(duration: Duration) -> String {
     let seconds = duration.as_secs();
     let minutes = seconds / 60;
     let remaining_seconds = seconds % 60;
-    return format!("{}:{}", minutes, remaining_seconds);
+    format!("{}:{}", minutes, remaining_seconds)
+}
+
+pub fn format_duration_human_readable(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    let mut s = String::new();
+    if hours > 0 {
+        s.push_str(&format!("{}h", hours));
+    }
+    if minutes > 0 {
+        s.push_str(&format!("{}m", minutes));
+    }
+    if seconds > 0 || s.is_empty() {
+        s.push_str(&format!("{}s", seconds));
+    }
+    s
+}
+
+/// A number of minutes as duration, an error when it doesn't fit.
+pub fn minutes_to_duration(minutes: u64) -> Result<Duration, String> {
+    minutes
+        .checked_mul(60)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration of {} minutes is too long", minutes))
+}
+
+/// `time + duration`, or the latest representable time when that overflows,
+/// so absurd durations from hand-edited files can't panic.
+pub fn add_saturating(time: DateTime<Utc>, duration: Duration) -> DateTime<Utc> {
+    ChronoDuration::from_std(duration)
+        .ok()
+        .and_then(|delta| time.checked_add_signed(delta))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Parses durations like `25m`, `1h30m` or `1h 30m 10s`. A bare number counts as minutes.
+pub fn parse_duration_human_readable(v: &str) -> Result<Duration, String> {
+    let input = v.trim().to_lowercase();
+    if let Ok(minutes) = input.parse::<u64>() {
+        return minutes_to_duration(minutes);
+    }
+
+    let mut total_secs: u64 = 0;
+    let mut number = String::new();
+
+    for c in input.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{}'", v))?;
+        number.clear();
+        let unit_secs = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(format!("invalid duration unit '{}' in '{}'", c, v)),
+        };
+        total_secs = value
+            .checked_mul(unit_secs)
+            .and_then(|secs| total_secs.checked_add(secs))
+            .ok_or_else(|| format!("duration '{}' is too long", v))?;
+    }
+
+    if !number.is_empty() || input.is_empty() {
+        return Err(format!("invalid duration '{}', expected e.g. 25m or 1h30m", v));
+    }
+    Ok(Duration::from_secs(total_secs))
+}
+
+pub fn serialize_duration_human_readable<S>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format_duration_human_readable(*duration))
+}
+
+struct DurationVisitor;
+
+impl<'de> Visitor<'de> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a duration like 25m or 1h30m, a number of minutes, or a map with secs and nanos")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        parse_duration_human_readable(v).map_err(de::Error::custom)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        minutes_to_duration(v).map_err(de::Error::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let minutes = u64::try_from(v)
+            .map_err(|_| de::Error::custom(format!("negative duration {}", v)))?;
+        minutes_to_duration(minutes).map_err(de::Error::custom)
+    }
+
+    // Files written before the human readable format store serde's default {secs, nanos} map
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut secs: u64 = 0;
+        let mut nanos: u32 = 0;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "secs" => secs = map.next_value()?,
+                "nanos" => nanos = map.next_value()?,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let secs = secs
+            .checked_add(u64::from(nanos / 1_000_000_000))
+            .ok_or_else(|| de::Error::custom("duration is too long"))?;
+        Ok(Duration::new(secs, nanos % 1_000_000_000))
+    }
+}
+
+pub fn deserialize_duration_human_readable<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DurationVisitor)
+}
+
+/// Parses a point in time like `09:45` (on the day of `now`), `2025-06-02 09:45` or
+/// `2025-06-02 09:45:00`.
+pub fn parse_time_or_date_time(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+    if let Ok(time) = NaiveTime::parse_from_str(input, "%H:%M") {
+        return Ok(now.date_naive().and_time(time).and_utc());
+    }
+    ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .map(|naive| naive.and_utc())
+        .ok_or_else(|| format!("invalid time '{}', expected e.g. 09:45 or 2025-06-02 09:45", input))
+}
+
+/// Resolves a date filter to an inclusive range of days. Accepts `2025-05-31`,
+/// `2025-05-01 - 2025-05-31` and the keywords `today`, `yesterday`, `this week`,
+/// `last week`, `this month` and `last month`.
+pub fn parse_date_range(input: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let input = input.trim();
+    let days_since_monday = today.weekday().num_days_from_monday() as i64;
+    let this_week_start = today - ChronoDuration::days(days_since_monday);
+    let this_month_start = today.with_day(1)?;
+
+    match input.to_lowercase().as_str() {
+        "today" => return Some((today, today)),
+        "yesterday" => {
+            let yesterday = today.pred_opt()?;
+            return Some((yesterday, yesterday));
+        }
+        "this week" => return Some((this_week_start, this_week_start + ChronoDuration::days(6))),
+        "last week" => {
+            let start = this_week_start - ChronoDuration::days(7);
+            return Some((start, start + ChronoDuration::days(6)));
+        }
+        "this month" => {
+            let next_month_start = this_month_start.checked_add_months(Months::new(1))?;
+            return Some((this_month_start, next_month_start.pred_opt()?));
+        }
+        "last month" => {
+            let start = this_month_start.checked_sub_months(Months::new(1))?;
+            return Some((start, this_month_start.pred_opt()?));
+        }
+        _ => {}
+    }
+
+    if let Some((start, end)) = input.split_once(" - ") {
+        let start = NaiveDate::parse_from_str(start.trim(), "%Y-%m-%d").ok()?;
+        let end = NaiveDate::parse_from_str(end.trim(), "%Y-%m-%d").ok()?;
+        return Some((start, end));
+    }
+
+    let date = NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()?;
+    Some((date, date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct WithDuration {
+        #[serde(deserialize_with = "deserialize_duration_human_readable")]
+        duration: Duration,
+    }
+
+    fn duration_from_yaml(yaml: &str) -> Result<Duration, serde_yaml::Error> {
+        serde_yaml::from_str::<WithDuration>(yaml).map(|d| d.duration)
+    }
+
+    #[test]
+    fn parses_human_readable_durations() {
+        assert_eq!(parse_duration_human_readable("25"), Ok(Duration::from_secs(25 * 60)));
+        assert_eq!(parse_duration_human_readable("1h 30m 10s"), Ok(Duration::from_secs(5410)));
+        assert!(parse_duration_human_readable("5x").is_err());
+        assert!(parse_duration_human_readable("").is_err());
+    }
+
+    #[test]
+    fn huge_durations_are_an_error_instead_of_overflowing() {
+        let max = u64::MAX.to_string();
+        assert!(parse_duration_human_readable(&max).is_err());
+        assert!(parse_duration_human_readable(&format!("{}h", max)).is_err());
+        assert!(parse_duration_human_readable(&format!("{}s 1s", max)).is_err());
+        assert!(minutes_to_duration(u64::MAX / 60 + 1).is_err());
+
+        assert!(duration_from_yaml(&format!("duration: {}", max)).is_err());
+        assert!(duration_from_yaml(&format!("duration: {}", i64::MAX)).is_err());
+        assert!(duration_from_yaml(&format!("duration:\n  secs: {}\n  nanos: 1000000000", max))
+            .is_err());
+        assert_eq!(
+            duration_from_yaml("duration:\n  secs: 1500\n  nanos: 0").unwrap(),
+            Duration::from_secs(1500)
+        );
+    }
 }
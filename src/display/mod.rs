@@ -1,7 +1,9 @@
+use crate::session::tags::{roll_up_tags, TagTotal};
 use crate::session::Session;
 use chrono::Duration as ChronoDuration;
 use comfy_table::{Attribute, Cell, ContentArrangement, Table};
 use std::error::Error;
+use std::time::Duration;
 
 use crate::date_time::duration_in_minutes;
 
@@ -16,9 +18,10 @@ pub fn print_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error>> {
         .set_content_arrangement(ContentArrangement::Dynamic);
 
     for session in sessions {
+        let duration = session.actual_duration();
         table.add_row(vec![
             Cell::new(session.description),
-            Cell::new(format!("{:?}", duration_in_minutes(session.duration))), // Format duration as needed
+            Cell::new(format!("{:?}", duration_in_minutes(duration))), // Format duration as needed
             Cell::new(session.start.format("%Y-%m-%d %H:%M:%S").to_string()),
         ]);
     }
@@ -28,14 +31,83 @@ pub fn print_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+pub fn print_trash_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error>> {
+    let mut table = Table::new();
+    table
+        .set_header(vec![
+            Cell::new("Id").add_attribute(Attribute::Bold),
+            Cell::new("Description").add_attribute(Attribute::Bold),
+            Cell::new("Start Time").add_attribute(Attribute::Bold),
+            Cell::new("Deleted At").add_attribute(Attribute::Bold),
+        ])
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    for session in sessions {
+        let deleted_at = session
+            .deleted_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        table.add_row(vec![
+            Cell::new(session.id),
+            Cell::new(session.description),
+            Cell::new(session.start.format("%Y-%m-%d %H:%M:%S").to_string()),
+            Cell::new(deleted_at),
+        ]);
+    }
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+pub fn print_tag_table(tag_totals: Vec<TagTotal>) -> Result<(), Box<dyn Error>> {
+    let mut table = Table::new();
+    table
+        .set_header(vec![
+            Cell::new("Tag").add_attribute(Attribute::Bold),
+            Cell::new("Sessions").add_attribute(Attribute::Bold),
+            Cell::new("Duration").add_attribute(Attribute::Bold),
+        ])
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    for total in tag_totals {
+        table.add_row(vec![
+            Cell::new(indented_tag(&total)),
+            Cell::new(total.sessions),
+            Cell::new(format_hours_minutes(total.duration)),
+        ]);
+    }
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+// Nested tags show their last segment below the parent
+fn indented_tag(total: &TagTotal) -> String {
+    let name = total.tag.rsplit('/').next().unwrap_or(&total.tag);
+    format!("{}{}", "  ".repeat(total.depth), name)
+}
+
+fn format_hours_minutes(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
 pub fn export_to_ascii_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error>> {
+    print!("{}", format_ascii_table(sessions));
+    Ok(())
+}
+
+pub fn format_ascii_table(sessions: Vec<Session>) -> String {
     let mut sorted_sessions = sessions;
-    sorted_sessions.sort_by(|a, b| a.start.cmp(&b.start));
+    sorted_sessions.sort_by_key(|s| s.start);
 
     let total_duration = sorted_sessions
         .iter()
         .fold(ChronoDuration::zero(), |acc, session| {
-            acc + ChronoDuration::from_std(session.duration).unwrap_or(ChronoDuration::zero())
+            acc + ChronoDuration::from_std(session.actual_duration())
+                .unwrap_or(ChronoDuration::zero())
         });
 
     let mut output = String::new();
@@ -46,10 +118,11 @@ pub fn export_to_ascii_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error
 
     // Sessions
     for (i, session) in sorted_sessions.iter().enumerate() {
+        let duration = session.actual_duration();
         let duration_formatted = format!(
             "{:02}:{:02}",
-            session.duration.as_secs() / 60,
-            session.duration.as_secs() % 60
+            duration.as_secs() / 60,
+            duration.as_secs() % 60
         );
 
         output.push_str(&format!(
@@ -68,7 +141,20 @@ pub fn export_to_ascii_table(sessions: Vec<Session>) -> Result<(), Box<dyn Error
         total_minutes % 60
     ));
 
-    print!("{}", output);
+    let tag_totals = roll_up_tags(&sorted_sessions);
+    if !tag_totals.is_empty() {
+        output.push('\n');
+        output.push_str("|              Tag             | Sessions |   Dauer   |\n");
+        output.push_str("|------------------------------|----------|-----------|\n");
+        for total in &tag_totals {
+            output.push_str(&format!(
+                "| {:28} | {:8} | {:9} |\n",
+                indented_tag(total),
+                total.sessions,
+                format_hours_minutes(total.duration),
+            ));
+        }
+    }
 
-    Ok(())
+    output
 }
@@ -3,16 +3,25 @@ mod command;
 mod config;
 mod date_time;
 mod display;
+mod query;
 mod session;
 mod tui;
 
-use crate::config::Config;
-use crate::session::{serialize_session, Session, SessionRatings, SessionService, SessionState};
+use crate::config::{Config, View};
+use crate::date_time::{
+    format_duration_human_readable, minutes_to_duration, parse_date_range,
+    parse_time_or_date_time,
+};
+use crate::query::Query;
+use crate::session::past::past_session;
+use crate::session::schedule::first_occurrence;
+use crate::session::{
+    serialize_session, Session, SessionRatings, SessionService, SessionState, SESSION_VERSION,
+};
 
-use chrono::{Duration as ChronoDuration, Utc};
-use command::Command;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use command::{Command, TagsCommand, TrashCommand};
 use dirs::home_dir;
-use home;
 use rand::Rng;
 use std::error::Error;
 use std::fs;
@@ -22,6 +31,8 @@ use std::thread;
 use std::time::Duration;
 use structopt::StructOpt;
 use tui::app::App;
+use tui::keymap::Keymap;
+use tui::theme::Theme;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "pomodoro")]
@@ -85,7 +96,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let pomodoro_session_dir = std::env::var("POMODORO_SESSION_DIR")
-        .unwrap_or_else(|_| config.pomodoro_config.pomodoro_session_dir);
+        .unwrap_or(config.pomodoro_config.pomodoro_session_dir.clone());
 
     let session_service = SessionService {
         pomodoro_session_dir,
@@ -93,8 +104,18 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     match opts.cmd {
         Command::Tui => {
+            let keymap = Keymap::from_config(&config.keymap)?;
+            let no_color = std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+            let theme = Theme::from_config(&config.theme, no_color)?;
             let sessions = session_service.load_sessions()?;
-            let mut app = App::new(sessions, session_service.pomodoro_session_dir.clone());
+            let mut app = App::new(
+                sessions,
+                session_service.pomodoro_session_dir.clone(),
+                config,
+                config_path,
+                keymap,
+                theme,
+            );
             app.run()?;
         }
         Command::InitSessionDir => {
@@ -104,13 +125,57 @@ fn main() -> Result<(), Box<dyn Error>> {
         Command::Start {
             duration,
             description,
+            preset,
+            overtime,
+            stopwatch,
         } => {
-            println!("Starting session: {} for {} minutes", description, duration);
+            let start = Utc::now();
+            let preset = match preset {
+                Some(name) => Some(
+                    config
+                        .find_preset(&name)
+                        .ok_or_else(|| format!("no preset named {}", name))?,
+                ),
+                None => None,
+            };
+            let mut session = match preset {
+                Some(preset) => preset.to_session(start),
+                None => Session::new(
+                    "no description".to_string(),
+                    Duration::from_secs(25 * 60),
+                    start,
+                ),
+            };
+            if let Some(description) = description {
+                session.description = description;
+            }
+            if let Some(minutes) = duration {
+                session.duration = minutes_to_duration(minutes)?;
+            }
+            if stopwatch {
+                session.duration = Duration::from_secs(0);
+            }
+            session.overtime =
+                overtime || config.pomodoro_config.overtime || session.duration.is_zero();
+            let minutes = session.duration.as_secs() / 60;
+
+            if session.is_stopwatch() {
+                println!("Starting stopwatch: {}", session.description);
+            } else {
+                println!("Starting session: {} for {} minutes", session.description, minutes);
+            }
 
-            println!("Duration: {} minutes", duration);
-            println!("Description: {}", description);
+            println!("Duration: {} minutes", minutes);
+            println!("Description: {}", session.description);
 
-            session_service.start_session(&description, duration * 60)?;
+            session_service.start_session(&session)?;
+
+            if let Some(preset) = preset {
+                let sessions = session_service.load_sessions()?;
+                if let Some(next_break) = preset.next_break(&sessions, start.date_naive()) {
+                    println!("Break afterwards: {}", format_duration_human_readable(next_break));
+                }
+            }
         }
         Command::Active => {
             println!("Showing all sessions");
@@ -126,7 +191,73 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
         }
 
+        Command::Stop => match session_service.stop_session(Utc::now())? {
+            Some(session) => println!(
+                "Stopped {} after {}",
+                session.description,
+                format_duration_human_readable(session.actual_duration())
+            ),
+            None => println!("No running session"),
+        },
+        Command::Schedule {
+            description,
+            at,
+            repeat,
+            duration,
+        } => {
+            let now = Utc::now();
+            let start = first_occurrence(&at, repeat, now)?;
+            let session = Session::new(description, minutes_to_duration(duration)?, start);
+            let session = session_service.schedule_session(session, repeat)?;
+            match repeat {
+                Some(repeat) => println!(
+                    "Scheduled {} {} from {}",
+                    session.description,
+                    repeat.label(),
+                    session.start.format("%Y-%m-%d %H:%M")
+                ),
+                None => println!(
+                    "Scheduled {} for {}",
+                    session.description,
+                    session.start.format("%Y-%m-%d %H:%M")
+                ),
+            }
+        }
+        Command::Log {
+            at,
+            duration,
+            description,
+            tags,
+        } => {
+            let now = Utc::now();
+            let start = parse_time_or_date_time(&at, now)?;
+            let mut session = past_session(description, start, duration, now)?;
+            session.tags = tags
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            let overlaps = session_service.log_session(&session)?;
+            println!(
+                "Logged {} from {} to {}",
+                session.description,
+                session.start.format("%Y-%m-%d %H:%M"),
+                session.end().format("%H:%M")
+            );
+            for other in overlaps {
+                eprintln!(
+                    "Warning: overlaps with {} from {} to {}",
+                    other.description,
+                    other.start.format("%Y-%m-%d %H:%M"),
+                    other.end().format("%H:%M")
+                );
+            }
+        }
         Command::Watch => loop {
+            if let Err(e) = session_service.start_due_sessions(Utc::now()) {
+                eprintln!("Error starting scheduled sessions: {}", e)
+            }
             match session_service.find_all_active_sessions() {
                 Ok(sessions) => {
                     session_service.update_pomodoro_status()?;
@@ -163,8 +294,10 @@ fn main() -> Result<(), Box<dyn Error>> {
             start_date,
             end_date,
             search_query,
+            query,
             export,
         } => {
+            let query = query.as_deref().map(Query::parse).transpose()?;
             use chrono::prelude::*;
 
             // Parse dates using NaiveDateTime first
@@ -175,7 +308,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             match (parsed_start, parsed_end) {
                 (Ok(start), Ok(end)) => {
-                    match session_service.find_sessions_in_range(start, end, search_query) {
+                    match session_service.find_sessions_in_range(start, end, search_query, query.as_ref()) {
                         Ok(sessions) => {
                             if export {
                                 display::export_to_ascii_table(sessions)?;
@@ -199,14 +332,16 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
         Command::FindSessionFromToday {
             search_query,
+            query,
             export,
         } => {
+            let query = query.as_deref().map(Query::parse).transpose()?;
             use chrono::prelude::*;
             let now = Utc::now();
             let start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
             let end = now.date_naive().and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-            match session_service.find_sessions_in_range(start, end, search_query) {
+            match session_service.find_sessions_in_range(start, end, search_query, query.as_ref()) {
                 Ok(sessions) => {
                     if export {
                         display::export_to_ascii_table(sessions)?;
@@ -219,8 +354,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
         Command::FindSessionFromYesterday {
             search_query,
+            query,
             export,
         } => {
+            let query = query.as_deref().map(Query::parse).transpose()?;
             use chrono::prelude::*;
             let now = Utc::now();
             let yesterday = (now - chrono::Duration::days(1)).date_naive();
@@ -235,7 +372,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .and_local_timezone(Utc)
                 .unwrap();
 
-            match session_service.find_sessions_in_range(start, end, search_query) {
+            match session_service.find_sessions_in_range(start, end, search_query, query.as_ref()) {
                 Ok(sessions) => {
                     if export {
                         display::export_to_ascii_table(sessions)?;
@@ -246,6 +383,124 @@ fn main() -> Result<(), Box<dyn Error>> {
                 Err(err) => println!("Error finding sessions: {}", err),
             }
         }
+        Command::Find {
+            view,
+            date,
+            query,
+            export,
+        } => {
+            let view = match view {
+                Some(name) => config
+                    .find_view(&name)
+                    .cloned()
+                    .ok_or_else(|| format!("No view named {}", name))?,
+                None => View::default(),
+            };
+
+            let date = date.unwrap_or(view.date);
+            let (start, end) = if date.trim().is_empty() {
+                (DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC)
+            } else {
+                let (first_day, last_day) = parse_date_range(&date, Utc::now().date_naive())
+                    .ok_or_else(|| format!("Invalid date filter: {}", date))?;
+                (
+                    first_day.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                    last_day.and_hms_opt(23, 59, 59).unwrap().and_utc(),
+                )
+            };
+
+            // The query of the view and the one given here must both match
+            let query = [view.search, query.unwrap_or_default()]
+                .iter()
+                .filter(|q| !q.trim().is_empty())
+                .map(|q| format!("({})", q))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let query = Query::parse(&query)?;
+
+            let mut sessions =
+                session_service.find_sessions_in_range(start, end, None, Some(&query))?;
+            sessions.retain(|s| s.state != SessionState::Deleted);
+            sessions.sort_by_key(|s| std::cmp::Reverse(s.start));
+            if export {
+                display::export_to_ascii_table(sessions)?;
+            } else {
+                display::print_table(sessions)?;
+            }
+        }
+        Command::Trash(trash_command) => match trash_command {
+            TrashCommand::List => {
+                display::print_trash_table(session_service.find_deleted_sessions()?)?;
+            }
+            TrashCommand::Restore { id } => {
+                let session = session_service.restore_session(&id)?;
+                println!("Restored: {}", session);
+            }
+            TrashCommand::Purge {
+                older_than_days,
+                yes,
+            } => {
+                let confirmed = yes
+                    || dialoguer::Confirm::new()
+                        .with_prompt("Permanently remove deleted sessions from disk?")
+                        .default(false)
+                        .interact()?;
+                if confirmed {
+                    let purged = session_service.purge_sessions(older_than_days)?;
+                    println!("Purged {} sessions", purged.len());
+                }
+            }
+        },
+        Command::Tags(tags_command) => match tags_command {
+            TagsCommand::List => {
+                display::print_tag_table(session_service.tag_totals()?)?;
+            }
+            TagsCommand::Rename { old, new } => {
+                let changed = session_service.rewrite_tags(&[old], Some(&new))?;
+                println!("Updated {} sessions", changed);
+            }
+            TagsCommand::Merge { tags, into } => {
+                let changed = session_service.rewrite_tags(&tags, Some(&into))?;
+                println!("Updated {} sessions", changed);
+            }
+            TagsCommand::Delete { tag, yes } => {
+                let confirmed = yes
+                    || dialoguer::Confirm::new()
+                        .with_prompt(format!("Remove the tag {} from all sessions?", tag))
+                        .default(false)
+                        .interact()?;
+                if confirmed {
+                    let changed = session_service.rewrite_tags(&[tag], None)?;
+                    println!("Updated {} sessions", changed);
+                }
+            }
+        },
+        Command::Migrate { dry_run } => {
+            let (migrations, backup_dir) = if dry_run {
+                (session_service.plan_migrations()?, None)
+            } else {
+                let (migrations, backup_dir) = session_service.migrate()?;
+                (migrations, Some(backup_dir))
+            };
+            if migrations.is_empty() {
+                println!("All session files are up to date (version {})", SESSION_VERSION);
+            } else if let Some(backup_dir) = backup_dir {
+                println!(
+                    "Migrated {} session files, originals saved in {}",
+                    migrations.len(),
+                    backup_dir.display()
+                );
+            } else {
+                for migration in &migrations {
+                    println!(
+                        "# version {} -> {}",
+                        migration.from_version, SESSION_VERSION
+                    );
+                    print!("{}", migration.diff());
+                }
+                println!("{} session files would be migrated", migrations.len());
+            }
+        }
         Command::GenerateTestData { number } => {
             let test_data_dir = "test-data";
             if !std::path::Path::new(test_data_dir).exists() {
@@ -258,7 +513,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             let mut rng = rand::rng();
             let now = Utc::now();
 
-            let descriptions = vec![
+            let descriptions = [
                 "Implement feature X",
                 "Fix bug Y",
                 "Code review",
@@ -268,7 +523,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 "Learning Rust",
                 "Setup environment",
             ];
-            let tags_pool = vec![
+            let tags_pool = [
                 "work", "personal", "urgent", "learning", "rust", "tui", "fun",
             ];
 
@@ -306,9 +561,6 @@ fn main() -> Result<(), Box<dyn Error>> {
                 };
 
                 let session = Session {
-                    description: desc,
-                    duration: Duration::from_secs(duration_minutes * 60),
-                    start: start_time,
                     tags: session_tags,
                     notes: if rng.random_bool(0.3) {
                         "Generated test note.".to_string()
@@ -317,9 +569,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                     },
                     state: SessionState::Done,
                     ratings,
+                    ..Session::new(desc, Duration::from_secs(duration_minutes * 60), start_time)
                 };
 
-                serialize_session(&session, test_data_dir, start_time)?;
+                serialize_session(&session, test_data_dir)?;
             }
             println!("Done.");
         }
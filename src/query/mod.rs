@@ -0,0 +1,515 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use std::time::Duration;
+
+use crate::date_time::parse_duration_human_readable;
+use crate::session::tags::tag_matches;
+use crate::session::{Session, SessionState};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RatingKind {
+    MentalEnergy,
+    PhysicalEnergy,
+    CognitiveLoad,
+    Motivation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Free text, fuzzy matched against description and tags
+    Text(String),
+    Tag(String),
+    State(SessionState),
+    Note(String),
+    Description(String),
+    Project(String),
+    Rating(RatingKind, Comparison, u8),
+    /// Compared with the time actually worked, not the planned duration
+    Duration(Comparison, Duration),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    All,
+    Term(Term),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+/// A parsed search query like `tag:rust -tag:meeting rating.motivation>=4 duration>30m`.
+///
+/// Terms next to each other must all match, `OR` combines alternatives, `-` or `NOT`
+/// negates a term and parentheses group. Words without a field are fuzzy matched
+/// against description and tags.
+pub struct Query {
+    pub expr: Expr,
+    matcher: SkimMatcherV2,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = if parser.tokens.is_empty() {
+            Expr::All
+        } else {
+            parser.parse_or()?
+        };
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected {}", token.describe()));
+        }
+        Ok(Query {
+            expr,
+            matcher: SkimMatcherV2::default(),
+        })
+    }
+
+    pub fn matches(&self, session: &Session) -> bool {
+        self.matches_expr(&self.expr, session)
+    }
+
+    fn matches_expr(&self, expr: &Expr, session: &Session) -> bool {
+        match expr {
+            Expr::All => true,
+            Expr::Term(term) => self.matches_term(term, session),
+            Expr::Not(inner) => !self.matches_expr(inner, session),
+            Expr::And(exprs) => exprs.iter().all(|e| self.matches_expr(e, session)),
+            Expr::Or(exprs) => exprs.iter().any(|e| self.matches_expr(e, session)),
+        }
+    }
+
+    fn matches_term(&self, term: &Term, session: &Session) -> bool {
+        match term {
+            Term::Text(text) => {
+                let text_to_search = format!("{} {}", session.description, session.tags.join(" "));
+                self.matcher.fuzzy_match(&text_to_search, text).is_some()
+            }
+            Term::Tag(tag) => session.tags.iter().any(|t| tag_matches(t, tag)),
+            Term::State(state) => &session.state == state,
+            Term::Note(text) => contains_ignore_case(&session.notes, text),
+            Term::Description(text) => contains_ignore_case(&session.description, text),
+            Term::Project(text) => contains_ignore_case(&session.project, text),
+            Term::Rating(kind, comparison, value) => match &session.ratings {
+                Some(ratings) => {
+                    let rating = match kind {
+                        RatingKind::MentalEnergy => ratings.mental_energy,
+                        RatingKind::PhysicalEnergy => ratings.physical_energy,
+                        RatingKind::CognitiveLoad => ratings.cognitive_load,
+                        RatingKind::Motivation => ratings.motivation,
+                    };
+                    comparison.holds(rating, *value)
+                }
+                None => false,
+            },
+            Term::Duration(comparison, duration) => {
+                comparison.holds(session.actual_duration(), *duration)
+            }
+        }
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Minus,
+    Word { text: String, quoted: bool },
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Minus => "'-'".to_string(),
+            Token::Word { text, .. } => format!("'{}'", text),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word { text, quoted: false } if text == keyword)
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token::Minus);
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        quoted = true;
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(c) => text.push(c),
+                                None => return Err("missing closing quote".to_string()),
+                            }
+                        }
+                    } else {
+                        text.push(c);
+                    }
+                }
+                tokens.push(Token::Word { text, quoted });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut alternatives = vec![self.parse_and()?];
+        while self.peek().is_some_and(|t| t.is_keyword("OR")) {
+            self.pos += 1;
+            alternatives.push(self.parse_and()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Expr::Or(alternatives)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                None | Some(Token::RParen) => break,
+                Some(t) if t.is_keyword("OR") => break,
+                Some(t) if t.is_keyword("AND") => {
+                    self.pos += 1;
+                    terms.push(self.parse_unary()?);
+                }
+                Some(_) => terms.push(self.parse_unary()?),
+            }
+        }
+
+        // Neighbouring free text words form one fuzzy pattern, like the plain search did
+        let mut merged: Vec<Expr> = Vec::new();
+        for term in terms {
+            match (merged.last_mut(), term) {
+                (Some(Expr::Term(Term::Text(previous))), Expr::Term(Term::Text(text))) => {
+                    previous.push(' ');
+                    previous.push_str(&text);
+                }
+                (_, term) => merged.push(term),
+            }
+        }
+
+        Ok(if merged.len() == 1 {
+            merged.remove(0)
+        } else {
+            Expr::And(merged)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_atom()?)))
+            }
+            Some(t) if t.is_keyword("NOT") => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr, String> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| "unexpected end of query".to_string())?;
+        self.pos += 1;
+        match token {
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err("missing ')'".to_string());
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Token::Word { text, quoted } => {
+                if quoted && !text.contains(':') {
+                    return Ok(Expr::Term(Term::Text(text)));
+                }
+                parse_term(&text).map(Expr::Term)
+            }
+            token => Err(format!("unexpected {}", token.describe())),
+        }
+    }
+}
+
+fn parse_term(word: &str) -> Result<Term, String> {
+    let Some(split_at) = word.find([':', '>', '<', '=']) else {
+        return Ok(Term::Text(word.to_string()));
+    };
+    let field = word[..split_at].to_lowercase();
+    let rest = &word[split_at..];
+
+    let (comparison, value) = if let Some(v) = rest.strip_prefix(">=") {
+        (Comparison::Ge, v)
+    } else if let Some(v) = rest.strip_prefix("<=") {
+        (Comparison::Le, v)
+    } else if let Some(v) = rest.strip_prefix('>') {
+        (Comparison::Gt, v)
+    } else if let Some(v) = rest.strip_prefix('<') {
+        (Comparison::Lt, v)
+    } else {
+        (Comparison::Eq, &rest[1..])
+    };
+    if value.is_empty() {
+        return Err(format!("missing value for '{}'", field));
+    }
+
+    let text_field = |term: fn(String) -> Term| {
+        if comparison == Comparison::Eq {
+            Ok(term(value.to_string()))
+        } else {
+            Err(format!("'{}' can only be compared with ':'", field))
+        }
+    };
+
+    match field.as_str() {
+        "tag" => text_field(Term::Tag),
+        "note" | "notes" => text_field(Term::Note),
+        "desc" | "description" => text_field(Term::Description),
+        "project" => text_field(Term::Project),
+        "state" => {
+            let state = match value.to_lowercase().as_str() {
+                "planned" => SessionState::Planned,
+                "running" => SessionState::Running,
+                "done" => SessionState::Done,
+                "canceled" | "cancelled" => SessionState::Canceled,
+                "deleted" => SessionState::Deleted,
+                _ => return Err(format!("unknown state '{}'", value)),
+            };
+            if comparison != Comparison::Eq {
+                return Err("'state' can only be compared with ':'".to_string());
+            }
+            Ok(Term::State(state))
+        }
+        "duration" => {
+            let duration = parse_duration_human_readable(value)?;
+            Ok(Term::Duration(comparison, duration))
+        }
+        _ => {
+            let Some(kind) = field.strip_prefix("rating.") else {
+                return Err(format!("unknown field '{}'", field));
+            };
+            let kind = match kind {
+                "mental" | "mental_energy" => RatingKind::MentalEnergy,
+                "physical" | "physical_energy" => RatingKind::PhysicalEnergy,
+                "cognitive" | "cognitive_load" => RatingKind::CognitiveLoad,
+                "motivation" => RatingKind::Motivation,
+                _ => return Err(format!("unknown rating '{}'", kind)),
+            };
+            let value: u8 = value
+                .parse()
+                .map_err(|_| format!("invalid rating '{}'", value))?;
+            Ok(Term::Rating(kind, comparison, value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{EndReason, SessionRatings};
+    use chrono::{TimeZone, Utc};
+
+    fn text(s: &str) -> Expr {
+        Expr::Term(Term::Text(s.to_string()))
+    }
+
+    fn tag(s: &str) -> Expr {
+        Expr::Term(Term::Tag(s.to_string()))
+    }
+
+    fn parse(input: &str) -> Expr {
+        Query::parse(input).unwrap().expr
+    }
+
+    fn error(input: &str) -> String {
+        Query::parse(input).err().unwrap()
+    }
+
+    fn session(description: &str, tags: &[&str]) -> Session {
+        let start = Utc.with_ymd_and_hms(2025, 5, 31, 14, 0, 0).unwrap();
+        Session {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            state: SessionState::Done,
+            ..Session::new(description.to_string(), Duration::from_secs(25 * 60), start)
+        }
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(parse("  "), Expr::All);
+        assert!(Query::parse("").unwrap().matches(&session("anything", &[])));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("tag:a tag:b OR tag:c"),
+            Expr::Or(vec![Expr::And(vec![tag("a"), tag("b")]), tag("c")])
+        );
+        assert_eq!(
+            parse("tag:a AND (tag:b OR tag:c)"),
+            Expr::And(vec![tag("a"), Expr::Or(vec![tag("b"), tag("c")])])
+        );
+        assert_eq!(parse("(tag:a)"), tag("a"));
+    }
+
+    #[test]
+    fn neighbouring_words_form_one_fuzzy_pattern() {
+        assert_eq!(parse("code review"), text("code review"));
+        assert_eq!(
+            parse("code tag:rust review"),
+            Expr::And(vec![text("code"), tag("rust"), text("review")])
+        );
+        let query = Query::parse("cod rev").unwrap();
+        assert!(query.matches(&session("code review", &[])));
+        assert!(!query.matches(&session("writing", &[])));
+    }
+
+    #[test]
+    fn minus_and_not_negate() {
+        assert_eq!(parse("-tag:a"), Expr::Not(Box::new(tag("a"))));
+        assert_eq!(parse("NOT NOT tag:a"), Expr::Not(Box::new(Expr::Not(Box::new(tag("a"))))));
+        assert_eq!(
+            parse("-(tag:a OR tag:b)"),
+            Expr::Not(Box::new(Expr::Or(vec![tag("a"), tag("b")])))
+        );
+        let query = Query::parse("-tag:meeting").unwrap();
+        assert!(query.matches(&session("code", &["rust"])));
+        assert!(!query.matches(&session("standup", &["meeting"])));
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_keywords_literal() {
+        assert_eq!(
+            parse(r#"note:"went well""#),
+            Expr::Term(Term::Note("went well".to_string()))
+        );
+        assert_eq!(parse(r#""OR""#), text("OR"));
+        assert_eq!(
+            parse(r#"desc:"code review" "two words""#),
+            Expr::And(vec![
+                Expr::Term(Term::Description("code review".to_string())),
+                text("two words"),
+            ])
+        );
+    }
+
+    #[test]
+    fn comparisons_and_ranges() {
+        assert_eq!(
+            parse("rating.motivation>=4"),
+            Expr::Term(Term::Rating(RatingKind::Motivation, Comparison::Ge, 4))
+        );
+        assert_eq!(
+            parse("duration>30m duration<=1h"),
+            Expr::And(vec![
+                Expr::Term(Term::Duration(Comparison::Gt, Duration::from_secs(30 * 60))),
+                Expr::Term(Term::Duration(Comparison::Le, Duration::from_secs(3600))),
+            ])
+        );
+
+        let mut rated = session("rated", &[]);
+        rated.ratings = Some(SessionRatings {
+            motivation: 4,
+            ..SessionRatings::default()
+        });
+        let query = Query::parse("rating.motivation>3 rating.motivation<5").unwrap();
+        assert!(query.matches(&rated));
+        assert!(!query.matches(&session("unrated", &[])));
+    }
+
+    #[test]
+    fn duration_compares_the_time_actually_worked() {
+        let mut stopped = session("stopped", &[]);
+        stopped.state = SessionState::Canceled;
+        stopped.end_reason = Some(EndReason::Canceled);
+        stopped.ended_at = Some(stopped.start + chrono::Duration::minutes(5));
+        assert!(Query::parse("duration<10m").unwrap().matches(&stopped));
+        assert!(!Query::parse("duration<10m").unwrap().matches(&session("full", &[])));
+    }
+
+    #[test]
+    fn malformed_queries_are_errors() {
+        assert_eq!(error("(tag:a"), "missing ')'");
+        assert_eq!(error("tag:a)"), "unexpected ')'");
+        assert_eq!(error(r#"note:"open"#), "missing closing quote");
+        assert_eq!(error("-"), "unexpected end of query");
+        assert_eq!(error("tag:"), "missing value for 'tag'");
+        assert_eq!(error("tag>a"), "'tag' can only be compared with ':'");
+        assert_eq!(error("state:sleeping"), "unknown state 'sleeping'");
+        assert_eq!(error("color:red"), "unknown field 'color'");
+        assert_eq!(error("rating.mood>3"), "unknown rating 'mood'");
+        assert_eq!(error("rating.motivation>high"), "invalid rating 'high'");
+        assert!(error("duration>soon").starts_with("invalid duration"));
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use super::{Session, SessionState};
+
+/// Where a session stands in the day's pomodoros.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CyclePosition {
+    /// 1-based number of the session among the day's pomodoros
+    pub today: usize,
+    /// 1-based position inside the current cycle
+    pub in_cycle: usize,
+    pub cycle_length: usize,
+}
+
+impl CyclePosition {
+    /// A long break follows the last pomodoro of a cycle.
+    pub fn is_last_in_cycle(&self) -> bool {
+        self.in_cycle == self.cycle_length
+    }
+
+    pub fn next_break(&self, short_break: Duration, long_break: Duration) -> Duration {
+        if self.is_last_in_cycle() {
+            long_break
+        } else {
+            short_break
+        }
+    }
+}
+
+/// Running and done sessions count as pomodoros, canceled, deleted and planned ones don't.
+pub fn is_pomodoro(session: &Session) -> bool {
+    matches!(session.state, SessionState::Running | SessionState::Done)
+}
+
+/// Number of pomodoros started on `day`.
+pub fn pomodoros_on(sessions: &[Session], day: NaiveDate) -> usize {
+    sessions
+        .iter()
+        .filter(|s| is_pomodoro(s) && s.start.date_naive() == day)
+        .count()
+}
+
+/// Position of `session` among the pomodoros started on its day, cycles restart every day.
+pub fn cycle_position(sessions: &[Session], session: &Session, cycle_length: usize) -> CyclePosition {
+    let day = session.start.date_naive();
+    let today = sessions
+        .iter()
+        .filter(|s| is_pomodoro(s) && s.start.date_naive() == day && s.start <= session.start)
+        .count()
+        .max(1);
+    let cycle_length = cycle_length.max(1);
+    CyclePosition {
+        today,
+        in_cycle: (today - 1) % cycle_length + 1,
+        cycle_length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn session(day: u32, hour: u32, state: SessionState) -> Session {
+        let start = Utc.with_ymd_and_hms(2025, 5, day, hour, 0, 0).unwrap();
+        Session {
+            state,
+            ..Session::new("work".to_string(), Duration::from_secs(25 * 60), start)
+        }
+    }
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 5, day).unwrap()
+    }
+
+    #[test]
+    fn only_running_and_done_sessions_count_as_pomodoros() {
+        let sessions = vec![
+            session(31, 9, SessionState::Done),
+            session(31, 10, SessionState::Canceled),
+            session(31, 11, SessionState::Deleted),
+            session(31, 12, SessionState::Planned),
+            session(31, 13, SessionState::Running),
+            session(30, 9, SessionState::Done),
+        ];
+        assert_eq!(pomodoros_on(&sessions, day(31)), 2);
+        assert_eq!(pomodoros_on(&sessions, day(30)), 1);
+        assert_eq!(pomodoros_on(&sessions, day(29)), 0);
+
+        let position = cycle_position(&sessions, &sessions[4], 4);
+        assert_eq!(position.today, 2);
+        assert_eq!(position.in_cycle, 2);
+    }
+
+    #[test]
+    fn cycles_wrap_and_restart_every_day() {
+        let sessions: Vec<Session> = (8..14)
+            .map(|hour| session(31, hour, SessionState::Done))
+            .chain([session(1, 8, SessionState::Done)])
+            .collect();
+        let positions: Vec<(usize, usize)> = sessions
+            .iter()
+            .map(|s| cycle_position(&sessions, s, 4))
+            .map(|p| (p.today, p.in_cycle))
+            .collect();
+        assert_eq!(positions, [(1, 1), (2, 2), (3, 3), (4, 4), (5, 1), (6, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn the_last_pomodoro_of_a_cycle_gets_the_long_break() {
+        let short = Duration::from_secs(5 * 60);
+        let long = Duration::from_secs(15 * 60);
+        let sessions: Vec<Session> =
+            (8..12).map(|hour| session(31, hour, SessionState::Done)).collect();
+        let breaks: Vec<Duration> = sessions
+            .iter()
+            .map(|s| cycle_position(&sessions, s, 4).next_break(short, long))
+            .collect();
+        assert_eq!(breaks, [short, short, short, long]);
+    }
+
+    #[test]
+    fn a_cycle_length_of_zero_counts_as_one() {
+        let sessions = vec![session(31, 9, SessionState::Done)];
+        let position = cycle_position(&sessions, &sessions[0], 0);
+        assert_eq!(position.cycle_length, 1);
+        assert!(position.is_last_in_cycle());
+    }
+}
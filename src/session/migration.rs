@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use similar::TextDiff;
+
+use super::storage;
+use super::{new_session_id, session_file_path, Session, SessionService, SESSION_VERSION};
+
+pub struct Migration {
+    pub from_version: u32,
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub old_content: String,
+    pub new_content: String,
+}
+
+impl Migration {
+    pub fn diff(&self) -> String {
+        TextDiff::from_lines(&self.old_content, &self.new_content)
+            .unified_diff()
+            .header(
+                &self.old_path.display().to_string(),
+                &self.new_path.display().to_string(),
+            )
+            .to_string()
+    }
+}
+
+impl SessionService {
+    /// Collects every session file whose content or name differs from the current layout.
+    pub fn plan_migrations(&self) -> Result<Vec<Migration>, Box<dyn Error>> {
+        let _lock = storage::lock_shared(&self.pomodoro_session_dir)?;
+        self.collect_migrations()
+    }
+
+    /// Plans and writes the migrations under one exclusive lock, so nothing written in
+    /// between is overwritten with a stale plan. Returns the migrations and the directory
+    /// holding a copy of every original file, which is only created when there are any.
+    pub fn migrate(&self) -> Result<(Vec<Migration>, PathBuf), Box<dyn Error>> {
+        let _lock = storage::lock_exclusive(&self.pomodoro_session_dir)?;
+        let migrations = self.collect_migrations()?;
+        let backup_dir = Path::new(&self.pomodoro_session_dir)
+            .join("backup")
+            .join(Utc::now().format("%Y%m%d%H%M%S").to_string());
+        if !migrations.is_empty() {
+            fs::create_dir_all(&backup_dir)?;
+            apply_migrations(&migrations, &backup_dir)?;
+        }
+        Ok((migrations, backup_dir))
+    }
+
+    /// Plans the migrations without taking the directory lock, the caller has to hold it.
+    fn collect_migrations(&self) -> Result<Vec<Migration>, Box<dyn Error>> {
+        let mut migrations = Vec::new();
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.pomodoro_session_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.sort();
+
+        for path in paths {
+            if path.extension().and_then(|s| s.to_str()) != Some("yaml") {
+                continue;
+            }
+
+            let old_content = fs::read_to_string(&path)?;
+            let value: serde_yaml::Value = serde_yaml::from_str(&old_content)?;
+            let from_version = value
+                .get("version")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as u32;
+            if from_version > SESSION_VERSION {
+                return Err(format!(
+                    "{} has version {}, this build only knows up to version {}",
+                    path.display(),
+                    from_version,
+                    SESSION_VERSION
+                )
+                .into());
+            }
+
+            let mut session: Session = serde_yaml::from_value(value)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            if session.id.is_empty() {
+                session.id = new_session_id(session.start);
+            }
+            session.version = SESSION_VERSION;
+
+            let new_content = serde_yaml::to_string(&session)?;
+            let new_path = session_file_path(&self.pomodoro_session_dir, &session.id);
+            if new_content == old_content && new_path == path {
+                continue;
+            }
+
+            migrations.push(Migration {
+                from_version,
+                old_path: path,
+                new_path,
+                old_content,
+                new_content,
+            });
+        }
+        Ok(migrations)
+    }
+}
+
+/// Writes the migrations after copying every original file to `backup_dir`, the caller has to
+/// hold the directory lock.
+fn apply_migrations(migrations: &[Migration], backup_dir: &Path) -> Result<(), Box<dyn Error>> {
+    for migration in migrations {
+        let filename = migration
+            .old_path
+            .file_name()
+            .ok_or("session file without a name")?;
+        fs::copy(&migration.old_path, backup_dir.join(filename))?;
+
+        storage::write_atomic(&migration.new_path, migration.new_content.as_bytes())?;
+        if migration.new_path != migration.old_path {
+            fs::remove_file(&migration.old_path)?;
+        }
+    }
+    Ok(())
+}
@@ -5,22 +5,39 @@ use std::time::{Duration, SystemTime};
 use chrono::{DateTime, Utc};
 use std::error::Error;
 use std::fs;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::date_time::{deserialize_human_readable, duration_in_minutes, serialize_human_readable};
-use std::fs::OpenOptions;
+use crate::date_time::{
+    add_saturating, deserialize_duration_human_readable, deserialize_human_readable,
+    deserialize_optional_human_readable, duration_in_minutes, serialize_duration_human_readable,
+    serialize_human_readable, serialize_optional_human_readable,
+};
+use crate::query::Query;
+use schedule::Repeat;
 use std::io;
+use ulid::Ulid;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum SessionState {
+    /// Scheduled for later, started by `start_due_sessions`
+    Planned,
     Running,
     Done,
     Deleted,
     Canceled,
 }
 
+/// Why a session stopped running.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum EndReason {
+    /// Ran its full duration, or was finished after overtime
+    Completed,
+    Canceled,
+    /// Cut short by something else, the time until then was still worked
+    Interrupted,
+    StoppedEarly,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct SessionRatings {
     pub mental_energy: u8,
@@ -30,13 +47,33 @@ pub struct SessionRatings {
     pub motivation: u8,
 }
 
+pub mod cycle;
+pub mod migration;
+pub mod past;
+pub mod schedule;
+pub mod storage;
+pub mod tags;
+pub mod trash;
+
+/// Version of the session file layout written by this build.
+/// Files without a `version` field are treated as version 0.
+pub const SESSION_VERSION: u32 = 1;
+
 fn default_state() -> SessionState {
     SessionState::Done
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Session {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub id: String,
     pub description: String,
+    #[serde(
+        serialize_with = "serialize_duration_human_readable",
+        deserialize_with = "deserialize_duration_human_readable"
+    )]
     pub duration: Duration,
     #[serde(
         serialize_with = "serialize_human_readable",
@@ -47,10 +84,34 @@ pub struct Session {
     pub tags: Vec<String>,
     #[serde(default)]
     pub notes: String,
+    #[serde(default)]
+    pub project: String,
     #[serde(default = "default_state")]
     pub state: SessionState,
     #[serde(default)]
     pub ratings: Option<SessionRatings>,
+    #[serde(
+        default,
+        serialize_with = "serialize_optional_human_readable",
+        deserialize_with = "deserialize_optional_human_readable"
+    )]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub state_before_delete: Option<SessionState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<Repeat>,
+    /// Keeps running past `duration` until stopped, a stopwatch has no duration at all
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overtime: bool,
+    /// When the session was actually stopped, `start + duration` is only what was planned
+    #[serde(
+        default,
+        serialize_with = "serialize_optional_human_readable",
+        deserialize_with = "deserialize_optional_human_readable"
+    )]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_reason: Option<EndReason>,
 }
 
 impl fmt::Display for Session {
@@ -66,22 +127,138 @@ impl fmt::Display for Session {
 }
 
 impl Session {
+    /// A fresh running session with a new id and no tags, notes or ratings.
+    pub fn new(description: String, duration: Duration, start: DateTime<Utc>) -> Session {
+        Session {
+            version: SESSION_VERSION,
+            id: new_session_id(start),
+            description,
+            duration,
+            start,
+            tags: Vec::new(),
+            notes: String::new(),
+            project: String::new(),
+            state: SessionState::Running,
+            ratings: None,
+            deleted_at: None,
+            state_before_delete: None,
+            repeat: None,
+            overtime: false,
+            ended_at: None,
+            end_reason: None,
+        }
+    }
+
+    /// Moves the session to the trash, remembering its state for `restore`.
+    pub fn delete(&mut self) {
+        if self.state != SessionState::Deleted {
+            self.state_before_delete = Some(self.state.clone());
+            self.state = SessionState::Deleted;
+            self.deleted_at = Some(Utc::now());
+        }
+    }
+
+    pub fn restore(&mut self) {
+        if self.state == SessionState::Deleted {
+            self.state = self.state_before_delete.take().unwrap_or(SessionState::Done);
+            self.deleted_at = None;
+        }
+    }
+
+    /// Time since the start, zero for sessions that start in the future.
     pub fn elapsed_duration(&self) -> Duration {
-        let now = Utc::now();
-        let duration_since_start = now.signed_duration_since(self.start);
-        Duration::new(
-            duration_since_start.num_seconds() as u64,
-            duration_since_start.num_nanoseconds().unwrap_or(0) as u32,
-        )
+        (Utc::now() - self.start).to_std().unwrap_or_default()
     }
 
     pub fn is_active(&self) -> bool {
         self.state == SessionState::Running
     }
 
+    /// A count-up session without a planned duration.
+    pub fn is_stopwatch(&self) -> bool {
+        self.overtime && self.duration.is_zero()
+    }
+
+    /// Time past the planned duration of a running overtime session.
+    pub fn overtime_duration(&self) -> Duration {
+        if self.overtime && self.state == SessionState::Running {
+            self.elapsed_duration().saturating_sub(self.duration)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// What was really worked: up to `ended_at` once stopped, the elapsed time while running
+    /// and nothing for planned sessions. Sessions that ended before `ended_at` was recorded
+    /// count with their planned duration.
+    pub fn actual_duration(&self) -> Duration {
+        match (self.ended_at, &self.state) {
+            (Some(ended_at), _) => (ended_at - self.start).to_std().unwrap_or_default(),
+            (None, SessionState::Planned) => Duration::from_secs(0),
+            (None, SessionState::Running) if self.overtime => self.elapsed_duration(),
+            (None, SessionState::Running) => self.elapsed_duration().min(self.duration),
+            (None, _) => self.duration,
+        }
+    }
+
+    /// When the session really ended or, while it runs, how far it got.
+    pub fn end(&self) -> DateTime<Utc> {
+        add_saturating(self.start, self.actual_duration())
+    }
+
+    /// When the session is planned to end, `start + duration`.
+    pub fn planned_end(&self) -> DateTime<Utc> {
+        add_saturating(self.start, self.duration)
+    }
+
+    /// Marks a running session done once its time is up.
+    pub fn complete(&mut self) {
+        if self.state == SessionState::Running {
+            self.state = SessionState::Done;
+            self.ended_at = Some(self.planned_end());
+            self.end_reason = Some(EndReason::Completed);
+        }
+    }
+
+    /// Completes a running session now, keeping the planned duration.
+    pub fn finish(&mut self, now: DateTime<Utc>) {
+        if self.state == SessionState::Running {
+            self.state = SessionState::Done;
+            self.ended_at = Some(now);
+            self.end_reason = Some(EndReason::Completed);
+        }
+    }
+
+    /// Completes a running session right now. Before its time is up the duration is trimmed
+    /// to what ran and it counts as stopped early, overtime sessions keep their duration.
+    pub fn stop(&mut self, now: DateTime<Utc>) {
+        if self.state != SessionState::Running {
+            return;
+        }
+        if now < self.planned_end() {
+            self.duration = (now - self.start).to_std().unwrap_or_default();
+            self.state = SessionState::Done;
+            self.ended_at = Some(now);
+            self.end_reason = Some(EndReason::StoppedEarly);
+        } else if self.overtime {
+            self.finish(now);
+        } else {
+            self.complete();
+        }
+    }
+
+    /// Ends a running or planned session without completing it.
+    pub fn cancel(&mut self, now: DateTime<Utc>, reason: EndReason) {
+        if matches!(self.state, SessionState::Running | SessionState::Planned) {
+            self.state = SessionState::Canceled;
+            self.ended_at = Some(now);
+            self.end_reason = Some(reason);
+        }
+    }
+
     pub fn remaining_duration(&self) -> Duration {
         let now = Utc::now();
-        let end = self.start + self.duration;
+        let end = self.planned_end();
         if end > now {
             (end - now).to_std().unwrap_or(Duration::from_secs(0))
         } else {
@@ -95,29 +272,9 @@ pub struct SessionService {
 }
 
 impl SessionService {
-    pub fn start_session(
-        &self,
-        description: &str,
-        duration_seconds: u64,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let start = SystemTime::now();
-        let start_date: DateTime<Utc> = start.into();
-
+    pub fn start_session(&self, session: &Session) -> Result<(), Box<dyn std::error::Error>> {
         println!("Using {}", &self.pomodoro_session_dir);
-
-        let session_dir = &self.pomodoro_session_dir;
-
-        let session = Session {
-            description: description.to_string(),
-            duration: Duration::new(duration_seconds, 0),
-            start: start_date,
-            tags: Vec::new(),
-            notes: String::new(),
-            state: SessionState::Running,
-            ratings: None,
-        };
-
-        serialize_session(&session, session_dir, start_date)?;
+        serialize_session(session, &self.pomodoro_session_dir)?;
         Ok(())
     }
 
@@ -126,8 +283,13 @@ impl SessionService {
         Ok(())
     }
 
+    /// Reads every session file without changing any of them. Legacy files without an id
+    /// get their file name as id, so writing them back replaces the same file; `migrate`
+    /// moves them to a real id. Files from a newer version are refused, writing them back
+    /// would drop whatever this build doesn't know.
     pub fn load_sessions(&self) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
         let mut sessions = Vec::new();
+        let _lock = storage::lock_shared(&self.pomodoro_session_dir)?;
         let paths = fs::read_dir(&self.pomodoro_session_dir)?;
 
         for path in paths {
@@ -135,39 +297,75 @@ impl SessionService {
 
             if path.extension().and_then(|s| s.to_str()) == Some("yaml") {
                 let contents = fs::read_to_string(&path)?;
-
-                let session: Session = serde_yaml::from_str(&contents)?;
-                sessions.push(session);
+                sessions.push(parse_session(&path, &contents)?);
             }
         }
         Ok(sessions)
     }
 
+    /// Cheap summary of the session files on disk, changes whenever a file is added, removed or rewritten.
+    /// Only `*-session.yaml` files count, the `status` file `watch` rewrites every second doesn't.
+    pub fn dir_fingerprint(&self) -> io::Result<(usize, Option<SystemTime>)> {
+        let mut count = 0;
+        let mut latest = None;
+
+        for entry in fs::read_dir(&self.pomodoro_session_dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            if name.ends_with("-session.yaml") {
+                count += 1;
+                let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                latest = latest.max(modified);
+            }
+        }
+        Ok((count, latest))
+    }
+
+    /// Stops the most recently started running session, see `Session::stop`.
+    pub fn stop_session(&self, now: DateTime<Utc>) -> Result<Option<Session>, Box<dyn Error>> {
+        let latest = self
+            .load_sessions()?
+            .into_iter()
+            .filter(|s| s.state == SessionState::Running && s.start <= now)
+            .max_by_key(|s| s.start);
+        let Some(latest) = latest else {
+            return Ok(None);
+        };
+
+        let _lock = storage::lock_exclusive(&self.pomodoro_session_dir)?;
+        // Re-read under the lock, the TUI may have completed it in the meantime
+        let mut session = match read_session_file(&self.pomodoro_session_dir, &latest.id)? {
+            Some(session) if session.state == SessionState::Running => session,
+            _ => return Ok(None),
+        };
+        session.stop(now);
+        write_session_file(&session, &self.pomodoro_session_dir)?;
+        Ok(Some(session))
+    }
+
+    /// Running sessions that still have time left, overtime sessions until they are stopped.
     pub fn find_all_active_sessions(&self) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
         let sessions = self.load_sessions()?;
         let now = Utc::now();
         let active_sessions = sessions
             .into_iter()
-            .filter(|session| session.start + session.duration > now)
+            .filter(|session| session.state == SessionState::Running)
+            .filter(|session| session.planned_end() > now || session.overtime)
             .collect();
         Ok(active_sessions)
     }
 
     pub fn update_pomodoro_status(&self) -> Result<(), io::Error> {
         if let Ok(sessions) = self.find_all_active_sessions() {
-            if let Some(session) = sessions.get(0) {
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .open(self.pomodoro_session_dir.clone() + "status")?;
-
-                writeln!(
-                    file,
-                    "{} - {}/{}",
+            if let Some(session) = sessions.first() {
+                let status = format!(
+                    "{} - {}/{}\n",
                     session.description,
                     duration_in_minutes(session.duration),
                     duration_in_minutes(session.elapsed_duration())
-                )?;
+                );
+                let status_path = Path::new(&self.pomodoro_session_dir).join("status");
+                storage::write_atomic(&status_path, status.as_bytes())?;
             }
         }
         Ok(())
@@ -178,23 +376,26 @@ impl SessionService {
         range_start: DateTime<Utc>,
         range_end: DateTime<Utc>,
         search_query: Option<String>,
+        query: Option<&Query>,
     ) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
         let sessions = self.load_sessions()?;
         let sessions_in_range = sessions
             .into_iter()
             .filter(|session| {
-                let session_end = session.start + session.duration;
+                let session_end = session.end();
                 let time_matches = session.start < range_end && session_end > range_start;
+                let query_matches = query.is_none_or(|q| q.matches(session));
 
                 match &search_query {
-                    Some(query) => {
+                    Some(search_query) => {
                         time_matches
+                            && query_matches
                             && session
                                 .description
                                 .to_lowercase()
-                                .contains(&query.to_lowercase())
+                                .contains(&search_query.to_lowercase())
                     }
-                    None => time_matches,
+                    None => time_matches && query_matches,
                 }
             })
             .collect();
@@ -202,16 +403,99 @@ impl SessionService {
     }
 }
 
-pub fn serialize_session(
-    session: &Session,
-    session_dir: &str,
-    start_date: DateTime<Utc>,
-) -> Result<(), Box<dyn Error>> {
-    let filename = format!("{}-session.yaml", start_date.format("%Y%m%d%H%M%S"));
-    let filepath = Path::new(session_dir).join(filename);
+pub fn new_session_id(start: DateTime<Utc>) -> String {
+    Ulid::from_datetime(start.into()).to_string()
+}
 
+/// Id of a session file written before sessions had one, e.g. `20250531140526`.
+fn legacy_session_id(path: &Path) -> String {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    stem.strip_suffix("-session").unwrap_or(stem).to_string()
+}
+
+/// Reads one session file the way `load_sessions` does, `None` when it is gone.
+/// Used to re-read a session under the lock before changing it.
+fn read_session_file(session_dir: &str, id: &str) -> Result<Option<Session>, Box<dyn Error>> {
+    let path = session_file_path(session_dir, id);
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse_session(&path, &contents).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+fn parse_session(path: &Path, contents: &str) -> Result<Session, Box<dyn Error>> {
+    let mut session: Session = serde_yaml::from_str(contents)?;
+    if session.version > SESSION_VERSION {
+        return Err(format!(
+            "{} has version {}, this build only knows up to version {}",
+            path.display(),
+            session.version,
+            SESSION_VERSION
+        )
+        .into());
+    }
+    if session.id.is_empty() {
+        session.id = legacy_session_id(path);
+    }
+    Ok(session)
+}
+
+pub fn session_file_path(session_dir: &str, id: &str) -> PathBuf {
+    Path::new(session_dir).join(format!("{}-session.yaml", id))
+}
+
+pub fn serialize_session(session: &Session, session_dir: &str) -> Result<(), Box<dyn Error>> {
+    let _lock = storage::lock_exclusive(session_dir)?;
+    write_session_file(session, session_dir)
+}
+
+pub fn remove_session_file(session_dir: &str, id: &str) -> Result<(), Box<dyn Error>> {
+    let _lock = storage::lock_exclusive(session_dir)?;
+    match fs::remove_file(session_file_path(session_dir, id)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(Box::new(e)),
+        _ => Ok(()),
+    }
+}
+
+/// Writes a session without taking the directory lock, the caller has to hold it.
+fn write_session_file(session: &Session, session_dir: &str) -> Result<(), Box<dyn Error>> {
+    let filepath = session_file_path(session_dir, &session.id);
+
+    // Whatever layout a session was read from, it is always written in the current one
+    let session = Session {
+        version: SESSION_VERSION,
+        ..session.clone()
+    };
     let serialized = serde_yaml::to_string(&session)?;
-    let mut file = File::create(filepath)?;
-    file.write_all(serialized.as_bytes())?;
+    storage::write_atomic(&filepath, serialized.as_bytes())?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(dir: &tempfile::TempDir) -> SessionService {
+        SessionService {
+            pomodoro_session_dir: dir.path().to_str().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_the_status_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir);
+        let session = Session::new("one".to_string(), Duration::from_secs(60), Utc::now());
+        service.start_session(&session).unwrap();
+        let before = service.dir_fingerprint().unwrap();
+        assert_eq!(before.0, 1);
+
+        storage::write_atomic(&dir.path().join("status"), b"one - 1:0/0:1\n").unwrap();
+        assert_eq!(service.dir_fingerprint().unwrap(), before);
+
+        let other = Session::new("two".to_string(), Duration::from_secs(60), Utc::now());
+        service.start_session(&other).unwrap();
+        assert_ne!(service.dir_fingerprint().unwrap(), before);
+    }
+}
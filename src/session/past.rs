@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::{EndReason, Session, SessionService, SessionState};
+
+/// A done session for work that already happened, e.g. when the timer wasn't started.
+/// It has to have a duration and be over by `now`.
+pub fn past_session(
+    description: String,
+    start: DateTime<Utc>,
+    duration: Duration,
+    now: DateTime<Utc>,
+) -> Result<Session, String> {
+    if duration.is_zero() {
+        return Err("a logged session needs a duration".to_string());
+    }
+    let end = chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| start.checked_add_signed(duration))
+        .ok_or_else(|| "a logged session can't be that long".to_string())?;
+    if end > now {
+        return Err(format!(
+            "a logged session has to be over, this one would end at {}",
+            end.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    Ok(Session {
+        state: SessionState::Done,
+        ended_at: Some(end),
+        end_reason: Some(EndReason::Completed),
+        ..Session::new(description, duration, start)
+    })
+}
+
+/// Sessions that share time with `session`. Deleted and planned sessions take no time.
+pub fn overlapping<'a>(sessions: &'a [Session], session: &Session) -> Vec<&'a Session> {
+    sessions
+        .iter()
+        .filter(|s| s.id != session.id)
+        .filter(|s| !matches!(s.state, SessionState::Deleted | SessionState::Planned))
+        .filter(|s| s.start < session.end() && s.end() > session.start)
+        .collect()
+}
+
+impl SessionService {
+    /// Writes a past session and returns the sessions it overlaps with.
+    pub fn log_session(&self, session: &Session) -> Result<Vec<Session>, Box<dyn Error>> {
+        let sessions = self.load_sessions()?;
+        let overlaps = overlapping(&sessions, session).into_iter().cloned().collect();
+        super::serialize_session(session, &self.pomodoro_session_dir)?;
+        Ok(overlaps)
+    }
+}
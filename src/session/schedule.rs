@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::date_time::parse_time_or_date_time;
+use super::storage;
+use super::{
+    new_session_id, read_session_file, write_session_file, EndReason, Session, SessionService,
+    SessionState,
+};
+
+/// How a planned session comes back after it started.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Repeat {
+    Daily,
+    Weekdays,
+    Weekly,
+}
+
+impl FromStr for Repeat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "daily" => Ok(Repeat::Daily),
+            "weekdays" => Ok(Repeat::Weekdays),
+            "weekly" => Ok(Repeat::Weekly),
+            _ => Err(format!("unknown repeat '{}', expected daily, weekdays or weekly", s)),
+        }
+    }
+}
+
+impl Repeat {
+    pub fn label(self) -> &'static str {
+        match self {
+            Repeat::Daily => "daily",
+            Repeat::Weekdays => "weekdays",
+            Repeat::Weekly => "weekly",
+        }
+    }
+
+    fn includes(self, time: DateTime<Utc>) -> bool {
+        self != Repeat::Weekdays || !matches!(time.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+
+    /// The first occurrence later than `after`, counted from `start` at its time of day.
+    pub fn next_after(self, start: DateTime<Utc>, after: DateTime<Utc>) -> DateTime<Utc> {
+        let step = match self {
+            Repeat::Weekly => ChronoDuration::days(7),
+            _ => ChronoDuration::days(1),
+        };
+        let mut next = start;
+        while next <= after || !self.includes(next) {
+            next += step;
+        }
+        next
+    }
+}
+
+/// When a newly scheduled session first starts: `at` itself if it is still ahead and on a day
+/// of the pattern, otherwise the next occurrence. A one-off session at a bare time that has
+/// passed today moves to tomorrow, a full date in the past is an error.
+pub fn first_occurrence(
+    at: &str,
+    repeat: Option<Repeat>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let start = parse_time_or_date_time(at, now)?;
+    let time_only = NaiveTime::parse_from_str(at.trim(), "%H:%M").is_ok();
+    match repeat {
+        Some(repeat) if start <= now || !repeat.includes(start) => {
+            Ok(repeat.next_after(start, now))
+        }
+        None if start <= now && time_only => Ok(Repeat::Daily.next_after(start, now)),
+        None if start <= now => Err(format!("{} is in the past", start.format("%Y-%m-%d %H:%M"))),
+        _ => Ok(start),
+    }
+}
+
+impl SessionService {
+    /// Writes a planned session that `start_due_sessions` starts once its time has come.
+    pub fn schedule_session(
+        &self,
+        mut session: Session,
+        repeat: Option<Repeat>,
+    ) -> Result<Session, Box<dyn Error>> {
+        session.state = SessionState::Planned;
+        session.repeat = repeat;
+        super::serialize_session(&session, &self.pomodoro_session_dir)?;
+        Ok(session)
+    }
+
+    /// Starts planned sessions that are due. A one-off session becomes the running session
+    /// itself, a repeating one starts a copy and moves on to its next occurrence. Sessions
+    /// that were over before anything checked are skipped, one-off ones are canceled.
+    pub fn start_due_sessions(&self, now: DateTime<Utc>) -> Result<Vec<Session>, Box<dyn Error>> {
+        let due: Vec<String> = self
+            .load_sessions()?
+            .into_iter()
+            .filter(|s| s.state == SessionState::Planned && s.start <= now)
+            .map(|s| s.id)
+            .collect();
+        if due.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let _lock = storage::lock_exclusive(&self.pomodoro_session_dir)?;
+        let mut started = Vec::new();
+        for id in due {
+            // Re-read under the lock, the TUI and `watch` may both be checking
+            let Some(planned) = read_session_file(&self.pomodoro_session_dir, &id)? else {
+                continue;
+            };
+            if planned.state != SessionState::Planned || planned.start > now {
+                continue;
+            }
+
+            let missed = planned.planned_end() <= now;
+            match planned.repeat {
+                Some(repeat) => {
+                    if !missed {
+                        let session = Session {
+                            id: new_session_id(planned.start),
+                            state: SessionState::Running,
+                            repeat: None,
+                            ..planned.clone()
+                        };
+                        write_session_file(&session, &self.pomodoro_session_dir)?;
+                        started.push(session);
+                    }
+                    let next = Session {
+                        start: repeat.next_after(planned.start, now),
+                        ..planned
+                    };
+                    write_session_file(&next, &self.pomodoro_session_dir)?;
+                }
+                None if missed => {
+                    // It never ran, so it ended right at its start
+                    let mut session = planned;
+                    let start = session.start;
+                    session.cancel(start, EndReason::Canceled);
+                    write_session_file(&session, &self.pomodoro_session_dir)?;
+                }
+                None => {
+                    let session = Session {
+                        state: SessionState::Running,
+                        ..planned
+                    };
+                    write_session_file(&session, &self.pomodoro_session_dir)?;
+                    started.push(session);
+                }
+            }
+        }
+        Ok(started)
+    }
+}
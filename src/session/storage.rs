@@ -0,0 +1,56 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+const LOCK_FILE: &str = ".lock";
+
+/// Advisory lock on a session directory, released when dropped.
+///
+/// The lock is taken through a separate file handle, so a process must not
+/// try to take it a second time while already holding it.
+pub struct SessionDirLock {
+    _file: File,
+}
+
+fn open_lock_file(session_dir: &str) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(Path::new(session_dir).join(LOCK_FILE))
+}
+
+/// Blocks until no other process reads or writes the session directory.
+pub fn lock_exclusive(session_dir: &str) -> io::Result<SessionDirLock> {
+    let file = open_lock_file(session_dir)?;
+    file.lock()?;
+    Ok(SessionDirLock { _file: file })
+}
+
+/// Blocks until no other process writes the session directory.
+pub fn lock_shared(session_dir: &str) -> io::Result<SessionDirLock> {
+    let file = open_lock_file(session_dir)?;
+    file.lock_shared()?;
+    Ok(SessionDirLock { _file: file })
+}
+
+/// Writes `contents` to a hidden temp file next to `path` and renames it into place,
+/// so readers see either the old or the new content but never a partial write.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let filename = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path without file name"))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", filename));
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if let Err(e) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::time::Duration;
+
+use super::storage;
+use super::{read_session_file, write_session_file, Session, SessionService, SessionState};
+
+/// Sessions, duration and nesting depth of a tag, including everything tagged below it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagTotal {
+    pub tag: String,
+    pub depth: usize,
+    pub sessions: usize,
+    pub duration: Duration,
+}
+
+fn is_same_or_child(tag: &str, parent: &str) -> bool {
+    tag == parent || tag.strip_prefix(parent).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Whether `tag` is `filter` or nested below it, so `client/acme` matches
+/// `client/acme/backend` but not `client/acmecorp`. Ignores case.
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    let filter = filter.trim_end_matches('/').to_lowercase();
+    is_same_or_child(&tag.to_lowercase(), &filter)
+}
+
+/// A tag and all its parents, `client/acme/backend` gives `client`, `client/acme`
+/// and `client/acme/backend`.
+pub fn tag_levels(tag: &str) -> Vec<&str> {
+    tag.match_indices('/')
+        .map(|(i, _)| &tag[..i])
+        .chain(std::iter::once(tag))
+        .collect()
+}
+
+/// Totals for every tag and each of its parents, children listed right after their parent.
+/// A session counts once per level even with several tags below the same parent.
+/// Deleted and planned sessions don't count.
+pub fn roll_up_tags(sessions: &[Session]) -> Vec<TagTotal> {
+    // Keyed by the path segments so `client/acme` sorts right after `client`, before `client-x`
+    let mut totals: BTreeMap<Vec<&str>, (usize, Duration)> = BTreeMap::new();
+    let counted = sessions
+        .iter()
+        .filter(|s| s.state != SessionState::Deleted && s.state != SessionState::Planned);
+    for session in counted {
+        let levels: HashSet<&str> = session.tags.iter().flat_map(|t| tag_levels(t)).collect();
+        for level in levels {
+            let total = totals.entry(level.split('/').collect()).or_default();
+            total.0 += 1;
+            total.1 += session.actual_duration();
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(segments, (sessions, duration))| TagTotal {
+            tag: segments.join("/"),
+            depth: segments.len() - 1,
+            sessions,
+            duration,
+        })
+        .collect()
+}
+
+/// Tags with the number of sessions using them, most used first. Deleted sessions don't count.
+pub fn count_tags(sessions: &[Session]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for session in sessions.iter().filter(|s| s.state != SessionState::Deleted) {
+        for tag in &session.tags {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Tags with every tag in `from` and the tags nested below it moved to `to`, or dropped
+/// without one. Duplicates that a merge produces are removed, the order is kept.
+fn replace_tags(tags: &[String], from: &[String], to: Option<&str>) -> Vec<String> {
+    let mut replaced: Vec<String> = Vec::new();
+    for tag in tags {
+        let parent = from.iter().find(|f| is_same_or_child(tag, f));
+        let tag = match (parent, to) {
+            (Some(parent), Some(to)) => format!("{}{}", to, &tag[parent.len()..]),
+            (Some(_), None) => continue,
+            (None, _) => tag.clone(),
+        };
+        if !replaced.contains(&tag) {
+            replaced.push(tag);
+        }
+    }
+    replaced
+}
+
+impl SessionService {
+    pub fn tag_totals(&self) -> Result<Vec<TagTotal>, Box<dyn Error>> {
+        Ok(roll_up_tags(&self.load_sessions()?))
+    }
+
+    /// Replaces the tags `from` with `to` in every session file, deleted sessions included.
+    /// Nested tags move along, renaming `client` to `customer` turns `client/acme` into
+    /// `customer/acme`. Without `to` the tags and their children are removed.
+    /// Returns the number of changed sessions.
+    pub fn rewrite_tags(&self, from: &[String], to: Option<&str>) -> Result<usize, Box<dyn Error>> {
+        let ids: Vec<String> = self
+            .load_sessions()?
+            .into_iter()
+            .filter(|s| s.tags.iter().any(|t| from.iter().any(|f| is_same_or_child(t, f))))
+            .map(|s| s.id)
+            .collect();
+
+        let _lock = storage::lock_exclusive(&self.pomodoro_session_dir)?;
+        let mut changed = 0;
+        for id in ids {
+            // Re-read under the lock so edits made since loading are kept
+            let Some(mut session) = read_session_file(&self.pomodoro_session_dir, &id)? else {
+                continue;
+            };
+            let tags = replace_tags(&session.tags, from, to);
+            if tags != session.tags {
+                session.tags = tags;
+                write_session_file(&session, &self.pomodoro_session_dir)?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
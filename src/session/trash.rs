@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::fs;
+
+use chrono::{Duration as ChronoDuration, Utc};
+
+use super::storage;
+use super::{
+    read_session_file, serialize_session, session_file_path, Session, SessionService, SessionState,
+};
+
+impl SessionService {
+    /// All soft-deleted sessions, most recently deleted first.
+    pub fn find_deleted_sessions(&self) -> Result<Vec<Session>, Box<dyn Error>> {
+        let mut sessions: Vec<Session> = self
+            .load_sessions()?
+            .into_iter()
+            .filter(|s| s.state == SessionState::Deleted)
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.deleted_at.unwrap_or(s.start)));
+        Ok(sessions)
+    }
+
+    /// Restores the deleted session whose id starts with `id_prefix`.
+    pub fn restore_session(&self, id_prefix: &str) -> Result<Session, Box<dyn Error>> {
+        let mut matches: Vec<Session> = self
+            .find_deleted_sessions()?
+            .into_iter()
+            .filter(|s| s.id.starts_with(id_prefix))
+            .collect();
+
+        match matches.len() {
+            0 => Err(format!("no deleted session with id {}", id_prefix).into()),
+            1 => {
+                let mut session = matches.remove(0);
+                session.restore();
+                serialize_session(&session, &self.pomodoro_session_dir)?;
+                Ok(session)
+            }
+            n => Err(format!("id {} is ambiguous, it matches {} sessions", id_prefix, n).into()),
+        }
+    }
+
+    /// Removes the files of deleted sessions. With `older_than_days` only sessions
+    /// deleted more than that many days ago are removed.
+    pub fn purge_sessions(
+        &self,
+        older_than_days: Option<u32>,
+    ) -> Result<Vec<Session>, Box<dyn Error>> {
+        let cutoff = older_than_days
+            .map(|days| {
+                ChronoDuration::try_days(days.into())
+                    .and_then(|age| Utc::now().checked_sub_signed(age))
+                    .ok_or_else(|| format!("{} days ago is out of range", days))
+            })
+            .transpose()?;
+        let candidates: Vec<Session> = self
+            .find_deleted_sessions()?
+            .into_iter()
+            .filter(|s| match cutoff {
+                // Sessions deleted before deleted_at was recorded fall back to their start
+                Some(cutoff) => s.deleted_at.unwrap_or(s.start) < cutoff,
+                None => true,
+            })
+            .collect();
+
+        let _lock = storage::lock_exclusive(&self.pomodoro_session_dir)?;
+        let mut purged = Vec::new();
+        for session in candidates {
+            if self.remove_if_deleted(&session.id)? {
+                purged.push(session);
+            }
+        }
+        Ok(purged)
+    }
+
+    // Re-reads the file under the lock, another process may have restored it in the meantime
+    fn remove_if_deleted(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        match read_session_file(&self.pomodoro_session_dir, id)? {
+            Some(session) if session.state == SessionState::Deleted => {}
+            _ => return Ok(false),
+        }
+        fs::remove_file(session_file_path(&self.pomodoro_session_dir, id))?;
+        Ok(true)
+    }
+}
@@ -1,21 +1,53 @@
-use chrono::{NaiveDate, Utc};
+use chrono::Utc;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     widgets::ListState,
     Frame, Terminal,
 };
-use std::{env, error::Error, fs, io, process::Command, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    env,
+    error::Error,
+    fs, io,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant, SystemTime},
+};
 
-use crate::session::{serialize_session, Session, SessionRatings, SessionState};
-use crate::tui::components::{filter_bar, info_pane, keybinds, overlay_bar, session_list, zen};
+use crate::session::cycle;
+use crate::session::past::{self, past_session};
+use crate::session::tags::count_tags;
+use crate::session::{
+    remove_session_file, serialize_session, EndReason, Session, SessionRatings, SessionService,
+    SessionState,
+};
+use crate::config::{self, Config, View};
+use crate::date_time::{
+    format_duration_human_readable, parse_date_range, parse_duration_human_readable,
+    parse_time_or_date_time,
+};
+use crate::display;
+use crate::query::Query;
+use crate::tui::components::{
+    filter_bar, info_pane, keybinds, overlay_bar, session_list, text_field, zen,
+};
 use crate::tui::events;
+use crate::tui::grouping::{sort_sessions, GroupBy, SortKey};
+use crate::tui::history::{Change, History};
+use crate::tui::keymap::Keymap;
+use crate::tui::text_input::TextInput;
+use crate::tui::theme::Theme;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum InputField {
@@ -27,6 +59,7 @@ pub enum InputField {
 pub enum CreationField {
     Duration,
     Description,
+    Start,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -44,23 +77,47 @@ pub enum Mode {
     Tagging,
     Creation(CreationField),
     Notes,
+    Project,
     DeleteConfirm,
     Rating(RatingField),
     FastFilter,
+    SaveView,
     Zen,
     PendingG,
+    Help,
+}
+
+/// Screen areas of the last drawn frame, used to map mouse clicks.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScreenAreas {
+    pub list: Rect,
+    pub date_input: Rect,
+    pub search_input: Rect,
+    pub ratings: Rect,
 }
 
 pub struct App {
     pub sessions: Vec<Session>,
     pub filtered_sessions: Vec<Session>,
-    pub date_input: String,
-    pub search_input: String,
-    pub tags_input: String,
-    pub notes_input: String,
+    pub date_input: TextInput,
+    pub search_input: TextInput,
+    pub query_error: Option<String>,
+    pub tags_input: TextInput,
+    pub notes_input: TextInput,
+    pub project_input: TextInput,
     
-    pub creation_duration: String,
-    pub creation_description: String,
+    pub creation_duration: TextInput,
+    pub creation_description: TextInput,
+    pub creation_start: TextInput,
+    /// The form logs a past session instead of starting one
+    pub creation_log: bool,
+    /// Preset chosen in the creation form
+    pub creation_preset: Option<usize>,
+    /// Tags and project for the new session, from a preset or a past description
+    pub creation_tags: Vec<String>,
+    pub creation_project: String,
+    /// Highlighted entry of the past descriptions dropdown
+    pub description_suggestion: Option<usize>,
 
     pub rating_mental: u8,
     pub rating_physical: u8,
@@ -68,21 +125,48 @@ pub struct App {
     pub rating_motivation: u8,
 
     pub mode: Mode,
+    pub sort_key: SortKey,
+    pub sort_reversed: bool,
+    pub group_by: GroupBy,
+    pub show_trash: bool,
+    pub history: History,
+    pub marked: HashSet<String>,
+    pub status_message: Option<String>,
     pub list_state: ListState,
+    /// Selection of the rendered list, which also contains group headers
+    pub display_list_state: ListState,
     pub session_dir: String,
+    pub config: Config,
+    pub config_path: PathBuf,
+    pub view_name_input: TextInput,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub help_scroll: u16,
+    /// Index of the highlighted tag completion
+    pub tag_suggestion: usize,
+    pub areas: ScreenAreas,
+    pub session_dir_fingerprint: Option<(usize, Option<SystemTime>)>,
+    pub last_reload_check: Instant,
 }
 
 impl App {
-    pub fn new(sessions: Vec<Session>, session_dir: String) -> App {
+    pub fn new(
+        sessions: Vec<Session>,
+        session_dir: String,
+        config: Config,
+        config_path: PathBuf,
+        keymap: Keymap,
+        theme: Theme,
+    ) -> App {
         let mut sessions = sessions;
-        sessions.sort_by(|a, b| b.start.cmp(&a.start));
+        sessions.sort_by_key(|s| Reverse(s.start));
         
         for session in sessions.iter_mut() {
-            if session.state == SessionState::Running {
+            if session.state == SessionState::Running && !session.overtime {
                 let remaining = session.remaining_duration();
                 if remaining.as_secs() == 0 {
-                    session.state = SessionState::Done;
-                    let _ = serialize_session(session, &session_dir, session.start);
+                    session.complete();
+                    let _ = serialize_session(session, &session_dir);
                 }
             }
         }
@@ -90,20 +174,47 @@ impl App {
         let mut app = App {
             filtered_sessions: Vec::new(),
             sessions,
-            date_input: String::new(),
-            search_input: String::new(),
-            tags_input: String::new(),
-            notes_input: String::new(),
-            creation_duration: String::new(),
-            creation_description: String::new(),
+            date_input: TextInput::new(),
+            search_input: TextInput::new(),
+            query_error: None,
+            tags_input: TextInput::new(),
+            notes_input: TextInput::multiline(),
+            project_input: TextInput::new(),
+            creation_duration: TextInput::new(),
+            creation_description: TextInput::new(),
+            creation_start: TextInput::new(),
+            creation_log: false,
+            creation_preset: None,
+            creation_tags: Vec::new(),
+            creation_project: String::new(),
+            description_suggestion: None,
             rating_mental: 0,
             rating_physical: 0,
             rating_cognitive: 0,
             rating_motivation: 0,
             mode: Mode::Navigation,
+            sort_key: SortKey::Start,
+            sort_reversed: false,
+            group_by: GroupBy::None,
+            show_trash: false,
+            history: History::default(),
+            marked: HashSet::new(),
+            status_message: None,
             list_state: ListState::default(),
+            display_list_state: ListState::default(),
             session_dir,
+            config,
+            config_path,
+            view_name_input: TextInput::new(),
+            keymap,
+            theme,
+            help_scroll: 0,
+            tag_suggestion: 0,
+            areas: ScreenAreas::default(),
+            session_dir_fingerprint: None,
+            last_reload_check: Instant::now(),
         };
+        app.session_dir_fingerprint = app.session_service().dir_fingerprint().ok();
 
         app.filter_sessions();
 
@@ -114,7 +225,54 @@ impl App {
         app
     }
 
+    fn session_service(&self) -> SessionService {
+        SessionService {
+            pomodoro_session_dir: self.session_dir.clone(),
+        }
+    }
+
+    pub fn selected_session(&self) -> Option<&Session> {
+        self.list_state
+            .selected()
+            .and_then(|idx| self.filtered_sessions.get(idx))
+    }
+
+    /// Picks up sessions that other processes added, changed or removed on disk.
+    pub fn reload_if_changed(&mut self) -> Result<(), Box<dyn Error>> {
+        // These modes act on the selected session, so it must not move away under the user
+        if matches!(
+            self.mode,
+            Mode::Tagging | Mode::Notes | Mode::Project | Mode::Rating(_) | Mode::DeleteConfirm
+        ) {
+            return Ok(());
+        }
+
+        let fingerprint = self.session_service().dir_fingerprint().ok();
+        if fingerprint == self.session_dir_fingerprint {
+            return Ok(());
+        }
+        self.session_dir_fingerprint = fingerprint;
+
+        let mut sessions = self.session_service().load_sessions()?;
+        sessions.sort_by_key(|s| Reverse(s.start));
+        self.sessions = sessions;
+        self.filter_sessions_keep_selection();
+        Ok(())
+    }
+
+    /// Like `filter_sessions`, but keeps the selected session selected if it is still visible.
+    pub fn filter_sessions_keep_selection(&mut self) {
+        let selected_id = self.selected_session().map(|s| s.id.clone());
+        self.filter_sessions();
+        if let Some(id) = selected_id {
+            self.select_session_by_id(&id);
+        }
+    }
+
     pub fn next(&mut self) {
+        if self.filtered_sessions.is_empty() {
+            return;
+        }
         let i = match self.list_state.selected() {
             Some(i) => {
                 if i >= self.filtered_sessions.len() - 1 {
@@ -129,6 +287,9 @@ impl App {
     }
 
     pub fn previous(&mut self) {
+        if self.filtered_sessions.is_empty() {
+            return;
+        }
         let i = match self.list_state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -142,66 +303,58 @@ impl App {
         self.list_state.select(Some(i));
     }
 
-    pub fn to_top(&mut self) {
+    pub fn go_to_top(&mut self) {
         if !self.filtered_sessions.is_empty() {
             self.list_state.select(Some(0));
         }
     }
 
-    pub fn to_bottom(&mut self) {
+    pub fn go_to_bottom(&mut self) {
         if !self.filtered_sessions.is_empty() {
             self.list_state.select(Some(self.filtered_sessions.len() - 1));
         }
     }
 
     pub fn filter_sessions(&mut self) {
-        let date_query = self.date_input.trim();
-        let search_query = self.search_input.trim();
-        let matcher = SkimMatcherV2::default();
+        let date_query = self.date_input.text().trim();
+        let date_range = parse_date_range(date_query, Utc::now().date_naive());
+        let query = match Query::parse(self.search_input.text().trim()) {
+            Ok(query) => {
+                self.query_error = None;
+                Some(query)
+            }
+            Err(e) => {
+                self.query_error = Some(e);
+                None
+            }
+        };
 
-        let filtered: Vec<Session> = self
+        let mut filtered: Vec<Session> = self
             .sessions
             .iter()
             .filter(|s| {
-                if s.state == SessionState::Deleted {
+                if (s.state == SessionState::Deleted) != self.show_trash {
                     return false;
                 }
 
                 let date_match = if date_query.is_empty() {
                     true
-                } else if date_query.contains(" - ") {
-                    let parts: Vec<&str> = date_query.split(" - ").collect();
-                    if parts.len() == 2 {
-                        if let (Ok(start), Ok(end)) = (
-                            NaiveDate::parse_from_str(parts[0], "%Y-%m-%d"),
-                            NaiveDate::parse_from_str(parts[1], "%Y-%m-%d"),
-                        ) {
-                            let d = s.start.date_naive();
-                            d >= start && d <= end
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
-                    }
-                } else if let Ok(date) = NaiveDate::parse_from_str(date_query, "%Y-%m-%d") {
-                    s.start.date_naive() == date
+                } else if let Some((start, end)) = date_range {
+                    let d = s.start.date_naive();
+                    d >= start && d <= end
                 } else {
                     false
                 };
 
-                let search_match = if search_query.is_empty() {
-                    true
-                } else {
-                    let text_to_search = format!("{} {}", s.description, s.tags.join(" "));
-                    matcher.fuzzy_match(&text_to_search, search_query).is_some()
-                };
+                // An unfinished query matches nothing until it parses again
+                let search_match = query.as_ref().is_some_and(|q| q.matches(s));
 
                 date_match && search_match
             })
             .cloned()
             .collect();
 
+        sort_sessions(&mut filtered, self.sort_key, self.sort_reversed, self.group_by);
         self.filtered_sessions = filtered;
         if !self.filtered_sessions.is_empty() {
             self.list_state.select(Some(0));
@@ -210,244 +363,734 @@ impl App {
         }
     }
 
-    pub fn save_tags(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get_mut(selected_idx) {
-                let new_tags: Vec<String> = self
-                    .tags_input
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                selected_session.tags = new_tags.clone();
-
-                if let Some(original_session) = self
-                    .sessions
-                    .iter_mut()
-                    .find(|s| s.start == selected_session.start)
-                {
-                    original_session.tags = new_tags;
-                }
+    fn select_session_by_id(&mut self, id: &str) {
+        if let Some(idx) = self.filtered_sessions.iter().position(|s| s.id == id) {
+            self.list_state.select(Some(idx));
+        }
+    }
 
-                serialize_session(selected_session, &self.session_dir, selected_session.start)?;
+    /// Writes the `after` side of every change to disk and into `sessions`.
+    fn apply_changes(&mut self, changes: &[Change]) -> Result<(), Box<dyn Error>> {
+        for change in changes {
+            match (&change.before, &change.after) {
+                (_, Some(after)) => {
+                    serialize_session(after, &self.session_dir)?;
+                    match self.sessions.iter_mut().find(|s| s.id == after.id) {
+                        Some(session) => *session = after.clone(),
+                        None => self.sessions.push(after.clone()),
+                    }
+                }
+                (Some(before), None) => {
+                    remove_session_file(&self.session_dir, &before.id)?;
+                    self.sessions.retain(|s| s.id != before.id);
+                }
+                (None, None) => {}
             }
         }
+        self.sessions.sort_by_key(|s| Reverse(s.start));
+        self.filter_sessions_keep_selection();
         Ok(())
     }
 
-    pub fn save_notes(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get_mut(selected_idx) {
-                let new_notes = self.notes_input.clone();
-                selected_session.notes = new_notes.clone();
-
-                if let Some(original_session) = self
-                    .sessions
-                    .iter_mut()
-                    .find(|s| s.start == selected_session.start)
-                {
-                    original_session.notes = new_notes;
-                }
+    /// Applies the changes and records them for undo.
+    fn change_sessions(&mut self, changes: Vec<Change>) -> Result<(), Box<dyn Error>> {
+        self.apply_changes(&changes)?;
+        self.history.record(changes);
+        Ok(())
+    }
+
+    /// Ids of the marked sessions, or of the selected one when nothing is marked.
+    pub fn target_ids(&self) -> HashSet<String> {
+        if self.marked.is_empty() {
+            self.selected_session().map(|s| s.id.clone()).into_iter().collect()
+        } else {
+            self.marked.clone()
+        }
+    }
 
-                serialize_session(selected_session, &self.session_dir, selected_session.start)?;
+    pub fn target_sessions(&self) -> Vec<Session> {
+        self.sessions_by_ids(&self.target_ids())
+    }
+
+    fn sessions_by_ids(&self, ids: &HashSet<String>) -> Vec<Session> {
+        self.sessions
+            .iter()
+            .filter(|s| ids.contains(&s.id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_session().map(|s| s.id.clone()) {
+            if !self.marked.remove(&id) {
+                self.marked.insert(id);
             }
+            self.next();
+        }
+    }
+
+    /// Marks every visible session, or unmarks them if they are all marked already.
+    pub fn toggle_mark_all(&mut self) {
+        let visible: Vec<String> = self.filtered_sessions.iter().map(|s| s.id.clone()).collect();
+        if visible.iter().all(|id| self.marked.contains(id)) {
+            for id in visible {
+                self.marked.remove(&id);
+            }
+        } else {
+            self.marked.extend(visible);
+        }
+    }
+
+    fn update_target_sessions<F>(&mut self, update: F) -> Result<(), Box<dyn Error>>
+    where
+        F: Fn(&mut Session),
+    {
+        self.update_sessions(&self.target_ids(), update)
+    }
+
+    /// Replaces the given sessions with the result of `update`, as one undo step.
+    /// Sessions `update` leaves as they were, e.g. canceling a done one, aren't part of it.
+    fn update_sessions<F>(&mut self, ids: &HashSet<String>, update: F) -> Result<(), Box<dyn Error>>
+    where
+        F: Fn(&mut Session),
+    {
+        let changes: Vec<Change> = self
+            .sessions_by_ids(ids)
+            .into_iter()
+            .filter_map(|before| {
+                let mut after = before.clone();
+                update(&mut after);
+                let unchanged =
+                    serde_yaml::to_string(&before).ok() == serde_yaml::to_string(&after).ok();
+                (!unchanged).then_some(Change {
+                    before: Some(before),
+                    after: Some(after),
+                })
+            })
+            .collect();
+        if changes.is_empty() {
+            return Ok(());
         }
+        self.change_sessions(changes)
+    }
+
+    /// Adds a session that starts now, as an overtime session if the config asks for it
+    /// and as a stopwatch without a duration.
+    fn start_new_session(&mut self, mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.overtime |= self.config.pomodoro_config.overtime || session.duration.is_zero();
+        self.add_session(session)
+    }
+
+    fn add_session(&mut self, session: Session) -> Result<(), Box<dyn Error>> {
+        let id = session.id.clone();
+        self.change_sessions(vec![Change {
+            before: None,
+            after: Some(session),
+        }])?;
+        self.select_session_by_id(&id);
         Ok(())
     }
 
-    pub fn save_ratings(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get_mut(selected_idx) {
-                let ratings = SessionRatings {
-                    mental_energy: self.rating_mental,
-                    physical_energy: self.rating_physical,
-                    cognitive_load: self.rating_cognitive,
-                    motivation: self.rating_motivation,
-                };
-                selected_session.ratings = Some(ratings.clone());
+    pub fn undo(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(changes) = self.history.undo() {
+            self.apply_changes(&changes)?;
+        }
+        Ok(())
+    }
 
-                if let Some(original_session) = self
-                    .sessions
-                    .iter_mut()
-                    .find(|s| s.start == selected_session.start)
-                {
-                    original_session.ratings = Some(ratings);
-                }
+    pub fn redo(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(changes) = self.history.redo() {
+            self.apply_changes(&changes)?;
+        }
+        Ok(())
+    }
 
-                serialize_session(selected_session, &self.session_dir, selected_session.start)?;
+    /// Replaces the tags of the selected session. With marked sessions the input
+    /// adds tags to all of them instead, and `-tag` removes a tag.
+    pub fn save_tags(&mut self) -> Result<(), Box<dyn Error>> {
+        let new_tags: Vec<String> = self
+            .tags_input
+            .text()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if self.marked.is_empty() {
+            return self.update_target_sessions(|session| session.tags = new_tags.clone());
+        }
+
+        self.update_target_sessions(|session| {
+            for tag in &new_tags {
+                if let Some(removed) = tag.strip_prefix('-') {
+                    session.tags.retain(|t| t != removed);
+                } else if !session.tags.contains(tag) {
+                    session.tags.push(tag.clone());
+                }
             }
+        })
+    }
+
+    /// Byte range of the comma separated tag under the cursor, without a leading `-`.
+    fn current_tag_range(&self) -> std::ops::Range<usize> {
+        let text = self.tags_input.text();
+        let cursor = self.tags_input.cursor();
+        let start = text[..cursor].rfind(',').map_or(0, |i| i + 1);
+        let end = text[cursor..].find(',').map_or(text.len(), |i| cursor + i);
+        let token = &text[start..end];
+        let leading = token.len() - token.trim_start().len();
+        let prefix = if token.trim_start().starts_with('-') { 1 } else { 0 };
+        start + leading + prefix..end
+    }
+
+    /// Existing tags matching the tag under the cursor, best fuzzy match first and most
+    /// used tags first without anything typed. Tags already in the input are left out.
+    pub fn tag_suggestions(&self) -> Vec<String> {
+        let range = self.current_tag_range();
+        let typed = self.tags_input.text()[range.clone()].trim();
+        let entered: HashSet<&str> = self
+            .tags_input
+            .text()
+            .split(',')
+            .map(|t| t.trim().trim_start_matches('-'))
+            .filter(|t| *t != typed)
+            .collect();
+
+        let matcher = SkimMatcherV2::default();
+        let mut suggestions: Vec<(i64, usize, String)> = count_tags(&self.sessions)
+            .into_iter()
+            .filter(|(tag, _)| !entered.contains(tag.as_str()))
+            .filter_map(|(tag, count)| {
+                let score = if typed.is_empty() {
+                    0
+                } else {
+                    matcher.fuzzy_match(&tag, typed)?
+                };
+                Some((score, count, tag))
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        suggestions.into_iter().take(5).map(|(_, _, tag)| tag).collect()
+    }
+
+    /// Replaces the tag under the cursor with the highlighted suggestion.
+    pub fn complete_tag(&mut self) {
+        let suggestions = self.tag_suggestions();
+        let Some(tag) = suggestions.get(self.tag_suggestion).or(suggestions.first()) else {
+            return;
+        };
+        let range = self.current_tag_range();
+        let at_end = range.end == self.tags_input.text().len();
+        let replacement = if at_end {
+            format!("{}, ", tag)
+        } else {
+            tag.clone()
+        };
+        // Keep a space after the previous comma
+        let replacement = match self.tags_input.text()[..range.start].chars().next_back() {
+            Some(',') => format!(" {}", replacement),
+            _ => replacement,
+        };
+        self.tags_input.replace_range(range, &replacement);
+        self.tag_suggestion = 0;
+    }
+
+    pub fn save_notes(&mut self) -> Result<(), Box<dyn Error>> {
+        // Notes are written per session, marks don't apply
+        let selected: HashSet<String> = self
+            .selected_session()
+            .map(|s| s.id.clone())
+            .into_iter()
+            .collect();
+        let new_notes = self.notes_input.text().to_string();
+        self.update_sessions(&selected, |session| session.notes = new_notes.clone())
+    }
+
+    pub fn save_project(&mut self) -> Result<(), Box<dyn Error>> {
+        let project = self.project_input.text().trim().to_string();
+        self.update_target_sessions(|session| session.project = project.clone())
+    }
+
+    /// The input that receives typed and pasted text in the current mode.
+    pub fn active_input(&mut self) -> Option<&mut TextInput> {
+        match self.mode {
+            Mode::Input(InputField::Date) => Some(&mut self.date_input),
+            Mode::Input(InputField::Search) => Some(&mut self.search_input),
+            Mode::Tagging => Some(&mut self.tags_input),
+            Mode::Notes => Some(&mut self.notes_input),
+            Mode::Project => Some(&mut self.project_input),
+            Mode::Creation(CreationField::Description) => Some(&mut self.creation_description),
+            Mode::Creation(CreationField::Duration) => Some(&mut self.creation_duration),
+            Mode::Creation(CreationField::Start) => Some(&mut self.creation_start),
+            Mode::SaveView => Some(&mut self.view_name_input),
+            _ => None,
         }
-        Ok(())
     }
 
+    /// Opens the rating form with the selected session's ratings, false without a selection.
+    pub fn start_rating(&mut self) -> bool {
+        let Some(session) = self.selected_session() else {
+            return false;
+        };
+        let ratings = session.ratings.clone().unwrap_or_default();
+        self.rating_mental = ratings.mental_energy;
+        self.rating_physical = ratings.physical_energy;
+        self.rating_cognitive = ratings.cognitive_load;
+        self.rating_motivation = ratings.motivation;
+        self.mode = Mode::Rating(RatingField::MentalEnergy);
+        true
+    }
+
+    pub fn set_rating(&mut self, field: &RatingField, value: u8) {
+        match field {
+            RatingField::MentalEnergy => self.rating_mental = value,
+            RatingField::PhysicalEnergy => self.rating_physical = value,
+            RatingField::CognitiveLoad => self.rating_cognitive = value,
+            RatingField::Motivation => self.rating_motivation = value,
+        }
+    }
+
+    pub fn save_ratings(&mut self) -> Result<(), Box<dyn Error>> {
+        let ratings = SessionRatings {
+            mental_energy: self.rating_mental,
+            physical_energy: self.rating_physical,
+            cognitive_load: self.rating_cognitive,
+            motivation: self.rating_motivation,
+        };
+        self.update_target_sessions(|session| session.ratings = Some(ratings.clone()))
+    }
+
+    /// Completes running sessions right now, see `Session::stop`.
+    pub fn finish_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = Utc::now();
+        self.update_target_sessions(|session| session.stop(now))
+    }
+
+    /// Cancels running sessions and planned ones, which also ends a repeating schedule.
     pub fn cancel_session(&mut self) -> Result<(), Box<dyn Error>> {
-         if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get_mut(selected_idx) {
-                if selected_session.state == SessionState::Running {
-                     selected_session.state = SessionState::Canceled;
-                     
-                     if let Some(original_session) = self.sessions.iter_mut().find(|s| s.start == selected_session.start) {
-                         original_session.state = SessionState::Canceled;
-                     }
-                     
-                     serialize_session(selected_session, &self.session_dir, selected_session.start)?;
-                }
+        let now = Utc::now();
+        self.update_target_sessions(|session| session.cancel(now, EndReason::Canceled))
+    }
+
+    /// Ends running sessions that were cut short, their time until now still counts.
+    pub fn interrupt_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = Utc::now();
+        self.update_target_sessions(|session| {
+            if session.state == SessionState::Running {
+                session.cancel(now, EndReason::Interrupted);
             }
-         }
-          Ok(())
+        })
     }
 
     pub fn delete_session(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get(selected_idx) {
-                let mut deleted_session = selected_session.clone();
-                deleted_session.state = SessionState::Deleted;
+        self.update_target_sessions(|session| session.delete())?;
+        self.marked.clear();
+        Ok(())
+    }
 
-                if let Some(original_session) = self.sessions.iter_mut().find(|s| s.start == selected_session.start) {
-                    original_session.state = SessionState::Deleted;
-                }
+    pub fn restore_session(&mut self) -> Result<(), Box<dyn Error>> {
+        self.update_target_sessions(|session| session.restore())?;
+        self.marked.clear();
+        Ok(())
+    }
 
-                serialize_session(&deleted_session, &self.session_dir, deleted_session.start)?;
-                self.filter_sessions();
-            }
+    /// Removes the target deleted sessions from disk, until undone.
+    pub fn purge_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let changes = self
+            .target_sessions()
+            .into_iter()
+            .filter(|s| s.state == SessionState::Deleted)
+            .map(|before| Change {
+                before: Some(before),
+                after: None,
+            })
+            .collect();
+        self.change_sessions(changes)?;
+        self.marked.clear();
+        Ok(())
+    }
+
+    pub fn apply_view(&mut self, index: usize) {
+        if let Some(view) = self.config.views.get(index).cloned() {
+            self.date_input.set(view.date);
+            self.search_input.set(view.search);
+            self.filter_sessions();
+            self.status_message = Some(format!("View: {}", view.name));
         }
+    }
+
+    /// Saves the current date and search filter as a named view in the config file.
+    pub fn save_view(&mut self) -> Result<(), Box<dyn Error>> {
+        let name = self.view_name_input.text().trim().to_string();
+        if name.is_empty() {
+            return Ok(());
+        }
+        let view = View {
+            name,
+            date: self.date_input.text().trim().to_string(),
+            search: self.search_input.text().trim().to_string(),
+        };
+        config::append_view(&self.config_path, &view)?;
+        self.status_message = Some(format!("Saved view: {}", view.name));
+        self.config.views.push(view);
+        Ok(())
+    }
+
+    pub fn cycle_sort_key(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.filter_sessions_keep_selection();
+    }
+
+    pub fn toggle_sort_order(&mut self) {
+        self.sort_reversed = !self.sort_reversed;
+        self.filter_sessions_keep_selection();
+    }
+
+    pub fn cycle_group_by(&mut self) {
+        self.group_by = self.group_by.next();
+        self.filter_sessions_keep_selection();
+    }
+
+    pub fn toggle_trash(&mut self) {
+        self.show_trash = !self.show_trash;
+        self.marked.clear();
+        self.filter_sessions();
+    }
+
+    /// Writes the marked sessions, or all visible ones, as ascii table to the exports dir.
+    pub fn export_sessions(&mut self) -> Result<(), Box<dyn Error>> {
+        let sessions = if self.marked.is_empty() {
+            self.filtered_sessions.clone()
+        } else {
+            self.target_sessions()
+        };
+        let count = sessions.len();
+
+        let export_dir = std::path::Path::new(&self.session_dir).join("exports");
+        fs::create_dir_all(&export_dir)?;
+        let path = export_dir.join(format!("{}-export.txt", Utc::now().format("%Y%m%d%H%M%S")));
+        fs::write(&path, display::format_ascii_table(sessions))?;
+
+        self.status_message = Some(format!("Exported {} sessions to {}", count, path.display()));
         Ok(())
     }
 
     pub fn duplicate_and_start_session(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get(selected_idx) {
-                let start = Utc::now();
-                let new_session = Session {
-                    description: selected_session.description.clone(),
-                    duration: selected_session.duration,
+        if let Some(selected_session) = self.selected_session() {
+            let start = Utc::now();
+            let new_session = Session {
+                tags: selected_session.tags.clone(),
+                notes: selected_session.notes.clone(),
+                ratings: selected_session.ratings.clone(),
+                ..Session::new(
+                    selected_session.description.clone(),
+                    selected_session.duration,
                     start,
-                    tags: selected_session.tags.clone(),
-                    notes: selected_session.notes.clone(),
-                    state: SessionState::Running,
-                    ratings: selected_session.ratings.clone(),
-                };
-
-                serialize_session(&new_session, &self.session_dir, start)?;
-                self.sessions.push(new_session);
-                self.sessions.sort_by(|a, b| b.start.cmp(&a.start));
-                self.filter_sessions();
-            }
+                )
+            };
+            self.start_new_session(new_session)?;
         }
         Ok(())
     }
 
+    /// The latest pomodoro, which Zen offers to start again when nothing is running.
+    pub fn last_pomodoro(&self) -> Option<&Session> {
+        self.sessions
+            .iter()
+            .filter(|s| cycle::is_pomodoro(s))
+            .max_by_key(|s| s.start)
+    }
+
+    /// Break after `session`, from its preset's break pattern or the configured short
+    /// and long breaks.
+    pub fn break_after(&self, session: &Session) -> Duration {
+        let day = session.start.date_naive();
+        let preset_break = self
+            .config
+            .presets
+            .iter()
+            .find(|p| p.description == session.description)
+            .and_then(|p| p.next_break(&self.sessions, day));
+        preset_break.unwrap_or_else(|| {
+            let pomodoro = &self.config.pomodoro_config;
+            cycle::cycle_position(&self.sessions, session, pomodoro.cycle_length)
+                .next_break(pomodoro.short_break, pomodoro.long_break)
+        })
+    }
+
+    /// Starts a session like the latest pomodoro with its tags and project.
+    pub fn start_next_session(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(last) = self.last_pomodoro() else {
+            return Ok(());
+        };
+        let session = Session {
+            tags: last.tags.clone(),
+            project: last.project.clone(),
+            ..Session::new(last.description.clone(), last.duration, Utc::now())
+        };
+        self.start_new_session(session)
+    }
+
     pub fn handle_edit_session(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     ) -> Result<(), Box<dyn Error>> {
-        if let Some(selected_idx) = self.list_state.selected() {
-            if let Some(selected_session) = self.filtered_sessions.get(selected_idx).cloned() {
-                disable_raw_mode()?;
-                execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-
-                let editor = env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-                let temp_path = env::temp_dir().join("polpettone_edit.yaml");
-                let yaml_content = serde_yaml::to_string(&selected_session)?;
-                fs::write(&temp_path, &yaml_content)?;
-
-                let status = Command::new(editor).arg(&temp_path).status()?;
-
-                enable_raw_mode()?;
-                execute!(terminal.backend_mut(), EnterAlternateScreen)?;
-                terminal.clear()?;
-
-                if status.success() {
-                    let new_content = fs::read_to_string(&temp_path)?;
-                    if let Ok(edited_session) = serde_yaml::from_str::<Session>(&new_content) {
-                        if edited_session.start != selected_session.start {
-                            let old_filename = format!(
-                                "{}-session.yaml",
-                                selected_session.start.format("%Y%m%d%H%M%S")
-                            );
-                            let old_path =
-                                std::path::Path::new(&self.session_dir).join(old_filename);
-                            if old_path.exists() {
-                                let _ = fs::remove_file(old_path);
-                            }
-                        }
+        if let Some(selected_session) = self.selected_session().cloned() {
+            disable_raw_mode()?;
+            execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
 
-                        if let Some(idx) = self
-                            .sessions
-                            .iter()
-                            .position(|s| s.start == selected_session.start)
-                        {
-                            self.sessions[idx] = edited_session.clone();
-                        }
-                        self.sessions.sort_by(|a, b| b.start.cmp(&a.start));
+            let editor = env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+            let temp_path = env::temp_dir().join("polpettone_edit.yaml");
+            let yaml_content = serde_yaml::to_string(&selected_session)?;
+            fs::write(&temp_path, &yaml_content)?;
 
-                        serialize_session(
-                            &edited_session,
-                            &self.session_dir,
-                            edited_session.start,
-                        )?;
+            let status = Command::new(editor).arg(&temp_path).status()?;
 
-                        self.filter_sessions();
-                    }
+            enable_raw_mode()?;
+            execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+            if self.config.pomodoro_config.mouse {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            }
+            terminal.clear()?;
+
+            if status.success() {
+                let new_content = fs::read_to_string(&temp_path)?;
+                if let Ok(mut edited_session) = serde_yaml::from_str::<Session>(&new_content) {
+                    // The id names the file on disk, so it can't be changed through the editor
+                    edited_session.id = selected_session.id.clone();
+
+                    self.change_sessions(vec![Change {
+                        before: Some(selected_session),
+                        after: Some(edited_session),
+                    }])?;
                 }
-                let _ = fs::remove_file(&temp_path);
             }
+            let _ = fs::remove_file(&temp_path);
         }
         Ok(())
     }
 
     pub fn create_session(&mut self) -> Result<(), Box<dyn Error>> {
-        let duration_mins: u64 = self.creation_duration.trim().parse().unwrap_or(25);
-        let description = self.creation_description.trim().to_string();
-        
+        let duration = parse_duration_human_readable(self.creation_duration.text())
+            .unwrap_or(Duration::from_secs(25 * 60));
+        let description = self.creation_description.text().trim().to_string();
+
         let start = Utc::now();
         let session = Session {
-            description,
-            duration: Duration::from_secs(duration_mins * 60),
-            start,
-            tags: Vec::new(),
-            notes: String::new(),
-            state: SessionState::Running,
-            ratings: None,
+            tags: self.creation_tags.clone(),
+            project: self.creation_project.clone(),
+            ..Session::new(description, duration, start)
         };
-        
-        serialize_session(&session, &self.session_dir, start)?;
-        
-        self.sessions.push(session);
-        self.sessions.sort_by(|a, b| b.start.cmp(&a.start));
-        
-        self.filter_sessions();
+        self.start_new_session(session)
+    }
+
+    /// Adds the done session described by the log form and reports the sessions it overlaps.
+    /// Returns false with the problem as status message when the form isn't a past session.
+    pub fn log_session(&mut self) -> Result<bool, Box<dyn Error>> {
+        let now = Utc::now();
+        let description = self.creation_description.text().trim().to_string();
+        let session = parse_duration_human_readable(self.creation_duration.text()).and_then(
+            |duration| {
+                let start = parse_time_or_date_time(self.creation_start.text(), now)?;
+                past_session(description, start, duration, now)
+            },
+        );
+        let session = match session {
+            Ok(session) => Session {
+                tags: self.creation_tags.clone(),
+                project: self.creation_project.clone(),
+                ..session
+            },
+            Err(e) => {
+                self.status_message = Some(e);
+                return Ok(false);
+            }
+        };
+
+        let overlaps: Vec<String> = past::overlapping(&self.sessions, &session)
+            .into_iter()
+            .map(|s| s.description.clone())
+            .collect();
+        let description = session.description.clone();
+        self.add_session(session)?;
+        self.status_message = Some(if overlaps.is_empty() {
+            format!("Logged {}", description)
+        } else {
+            format!("Logged {}, overlaps with {}", description, overlaps.join(", "))
+        });
+        Ok(true)
+    }
+
+    /// Opens the creation form for a session that already happened, ending now by default.
+    pub fn start_logging(&mut self) {
+        self.start_creation();
+        self.creation_log = true;
+        let start = Utc::now() - chrono::Duration::minutes(25);
+        self.creation_start.set(start.format("%Y-%m-%d %H:%M").to_string());
+    }
+
+    /// Opens the creation form with the most recent description and nothing else carried over.
+    pub fn start_creation(&mut self) {
+        self.creation_log = false;
+        self.creation_preset = None;
+        self.creation_tags.clear();
+        self.creation_project.clear();
+        self.description_suggestion = None;
+        self.creation_duration.set("25");
+        let description = self.sessions.first().map(|s| s.description.clone());
+        self.creation_description.set(description.unwrap_or_default());
+        self.mode = Mode::Creation(CreationField::Description);
+    }
+
+    /// Past descriptions fuzzy matching the creation form, each with its latest session.
+    /// Frequently and recently used descriptions come first, a session from today counts
+    /// fully and older ones less the longer ago they were.
+    pub fn description_suggestions(&self) -> Vec<&Session> {
+        let typed = self.creation_description.text().trim();
+        let matcher = SkimMatcherV2::default();
+        let today = Utc::now().date_naive();
+
+        let mut ranked: HashMap<&str, (f64, &Session)> = HashMap::new();
+        for session in &self.sessions {
+            if session.state == SessionState::Deleted || session.description.is_empty() {
+                continue;
+            }
+            let days_ago = (today - session.start.date_naive()).num_days().max(0);
+            let weight = 1.0 / (1.0 + days_ago as f64);
+            let entry = ranked.entry(&session.description).or_insert((0.0, session));
+            entry.0 += weight;
+            if session.start > entry.1.start {
+                entry.1 = session;
+            }
+        }
+
+        let mut suggestions: Vec<(f64, &Session)> = ranked
+            .into_values()
+            .filter(|(_, s)| {
+                typed.is_empty() || matcher.fuzzy_match(&s.description, typed).is_some()
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.start.cmp(&a.1.start)));
+        suggestions.into_iter().take(5).map(|(_, s)| s).collect()
+    }
+
+    /// Fills the creation form with the highlighted past description, its tags and duration.
+    pub fn complete_description(&mut self) {
+        let Some(idx) = self.description_suggestion.take() else {
+            return;
+        };
+        let Some(session) = self.description_suggestions().get(idx).map(|s| (*s).clone()) else {
+            return;
+        };
+        self.creation_preset = None;
+        self.creation_description.set(session.description);
+        self.creation_duration
+            .set(format_duration_human_readable(session.duration));
+        self.creation_tags = session.tags;
+        self.creation_project.clear();
+    }
+
+    /// Fills the creation form with the next or previous preset, after the last one
+    /// the form is back to no preset.
+    pub fn cycle_creation_preset(&mut self, forward: bool) {
+        let count = self.config.presets.len();
+        if count == 0 {
+            return;
+        }
+        self.creation_preset = match (self.creation_preset, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(count - 1),
+            (Some(i), true) if i + 1 < count => Some(i + 1),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            _ => None,
+        };
+        self.description_suggestion = None;
+        match self.creation_preset.map(|i| &self.config.presets[i]) {
+            Some(preset) => {
+                self.creation_description.set(preset.description.clone());
+                self.creation_duration
+                    .set(format_duration_human_readable(preset.duration));
+                self.creation_tags = preset.tags.clone();
+                self.creation_project = preset.project.clone();
+            }
+            None => {
+                self.creation_tags.clear();
+                self.creation_project.clear();
+            }
+        }
+    }
+
+    /// Starts a session from the preset at `idx` and shows the break that follows it.
+    pub fn start_preset(&mut self, idx: usize) -> Result<(), Box<dyn Error>> {
+        let Some(preset) = self.config.presets.get(idx).cloned() else {
+            return Ok(());
+        };
+        let start = Utc::now();
+        self.start_new_session(preset.to_session(start))?;
+        self.status_message = Some(match preset.next_break(&self.sessions, start.date_naive()) {
+            Some(next_break) => format!(
+                "Started {}, break afterwards: {}",
+                preset.name,
+                format_duration_human_readable(next_break)
+            ),
+            None => format!("Started {}", preset.name),
+        });
         Ok(())
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.config.pomodoro_config.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
         loop {
             let mut changed = false;
             for session in self.sessions.iter_mut() {
-                if session.state == SessionState::Running && session.remaining_duration().as_secs() == 0 {
-                    session.state = SessionState::Done;
-                    let _ = serialize_session(session, &self.session_dir, session.start);
+                if session.state == SessionState::Running
+                    && !session.overtime
+                    && session.remaining_duration().as_secs() == 0
+                {
+                    session.complete();
+                    let _ = serialize_session(session, &self.session_dir);
                     changed = true;
                 }
             }
             if changed {
-                self.filter_sessions();
+                self.filter_sessions_keep_selection();
+            }
+
+            if self.last_reload_check.elapsed() >= Duration::from_secs(1) {
+                self.last_reload_check = Instant::now();
+                if let Ok(started) = self.session_service().start_due_sessions(Utc::now()) {
+                    if let Some(session) = started.first() {
+                        self.status_message =
+                            Some(format!("Started scheduled session: {}", session.description));
+                    }
+                }
+                // A half synced file must not take down the TUI, the next change retries
+                let _ = self.reload_if_changed();
             }
 
             terminal.draw(|f| ui(f, self))?;
 
             if event::poll(Duration::from_millis(250))? {
-                if let Event::Key(key) = event::read()? {
-                    if !events::handle_key_event(key, self, &mut terminal)? {
-                        break;
+                match event::read()? {
+                    Event::Key(key) => {
+                        let keep_running = events::handle_key_event(key, self, &mut terminal)?;
+                        if !keep_running {
+                            break;
+                        }
                     }
+                    Event::Mouse(mouse) if mouse.kind != MouseEventKind::Moved => {
+                        events::handle_mouse_event(mouse, self)?;
+                    }
+                    Event::Paste(text) => events::handle_paste(&text, self),
+                    _ => {}
                 }
             }
         }
@@ -456,7 +1099,8 @@ impl App {
         execute!(
             terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste
         )?;
         terminal.show_cursor()?;
 
@@ -466,8 +1110,7 @@ impl App {
 
 fn ui(f: &mut Frame, app: &mut App) {
     if app.mode == Mode::Zen {
-        let running_session = app.sessions.iter().find(|s| s.state == SessionState::Running);
-        zen::render(f, running_session);
+        zen::render(f, app);
         return;
     }
 
@@ -485,7 +1128,7 @@ fn ui(f: &mut Frame, app: &mut App) {
             Constraint::Min(0),    
             Constraint::Length(3), 
         ]
-    } else if app.mode == Mode::FastFilter {
+    } else if app.mode == Mode::FastFilter || app.mode == Mode::SaveView {
         vec![
             Constraint::Length(3), 
             Constraint::Min(0),    
@@ -511,7 +1154,7 @@ fn ui(f: &mut Frame, app: &mut App) {
         (Some(chunks[1]), chunks[2], None, chunks[3])
     } else if app.mode == Mode::DeleteConfirm {
         (Some(chunks[1]), chunks[2], None, chunks[3])
-    } else if app.mode == Mode::FastFilter {
+    } else if app.mode == Mode::FastFilter || app.mode == Mode::SaveView {
         (None, chunks[1], Some(chunks[2]), chunks[3])
     } else {
         (None, chunks[1], None, chunks[2])
@@ -519,6 +1162,7 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     // --- Filter Bar ---
     filter_bar::render(f, top_chunk, app);
+    (app.areas.date_input, app.areas.search_input) = filter_bar::input_areas(top_chunk);
 
     // --- Overlay Bar (Creation or Delete Confirm) ---
     if let Some(m_chunk) = middle_chunk {
@@ -538,12 +1182,29 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     // --- Info Pane (Ratings, Tags, Notes) ---
     info_pane::render(f, right_pane_area, app);
+    app.areas.ratings = info_pane::rating_area(right_pane_area);
     
     // --- Keybinds & Fast Filter ---
     if let Some(chunk) = fast_filter_chunk {
-        f.render_widget(keybinds::render_fast_filter(), chunk);
+        if app.mode == Mode::SaveView {
+            let save_view = keybinds::render_save_view(&app.view_name_input, chunk, &app.theme);
+            f.render_widget(save_view, chunk);
+        } else {
+            f.render_widget(keybinds::render_fast_filter(&app.config.views, &app.theme), chunk);
+        }
+    }
+    f.render_widget(
+        keybinds::render_keybinds(&app.keymap, &app.config.presets, &app.theme),
+        keybinds_chunk,
+    );
+
+    if let Some(m_chunk) = middle_chunk {
+        overlay_bar::render_description_suggestions(f, m_chunk, app);
+    }
+
+    if app.mode == Mode::Help {
+        keybinds::render_help(f, &app.keymap, &app.theme, app.help_scroll);
     }
-    f.render_widget(keybinds::render_keybinds(), keybinds_chunk);
 
     // --- Cursor Handling ---
     if let Some((x, y)) = filter_bar::get_cursor_position(top_chunk, app) {
@@ -554,5 +1215,326 @@ fn ui(f: &mut Frame, app: &mut App) {
         }
     } else if let Some((x, y)) = info_pane::get_cursor_position(right_pane_area, app) {
         f.set_cursor_position((x, y));
+    } else if let (Some(chunk), Mode::SaveView) = (fast_filter_chunk, &app.mode) {
+        f.set_cursor_position(text_field::cursor_position(&app.view_name_input, chunk));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn test_app(dir: &tempfile::TempDir, sessions: Vec<Session>) -> App {
+        let session_dir = dir.path().to_str().unwrap().to_string();
+        for session in &sessions {
+            serialize_session(session, &session_dir).unwrap();
+        }
+        let config: Config = toml::from_str(&format!(
+            "[pomodoro_config]\npomodoro_session_dir = \"{}\"\n",
+            session_dir
+        ))
+        .unwrap();
+        App::new(
+            sessions,
+            session_dir,
+            config,
+            dir.path().join("config.toml"),
+            Keymap::default(),
+            Theme::default(),
+        )
+    }
+
+    pub(crate) fn done_session(description: &str, minutes_ago: i64) -> Session {
+        let start = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        Session {
+            state: SessionState::Done,
+            ..Session::new(description.to_string(), Duration::from_secs(60), start)
+        }
+    }
+
+    #[test]
+    fn reload_picks_up_sessions_written_elsewhere_and_keeps_the_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, vec![done_session("old", 60), done_session("older", 120)]);
+        app.next();
+        assert_eq!(app.selected_session().unwrap().description, "older");
+
+        serialize_session(&done_session("new", 5), &app.session_dir).unwrap();
+        app.reload_if_changed().unwrap();
+        assert_eq!(app.sessions.len(), 3);
+        assert_eq!(app.selected_session().unwrap().description, "older");
+    }
+
+    #[test]
+    fn reload_waits_while_editing_the_selected_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, vec![done_session("old", 60)]);
+        app.mode = Mode::Notes;
+
+        serialize_session(&done_session("new", 5), &app.session_dir).unwrap();
+        app.reload_if_changed().unwrap();
+        assert_eq!(app.sessions.len(), 1);
+
+        app.mode = Mode::Navigation;
+        app.reload_if_changed().unwrap();
+        assert_eq!(app.sessions.len(), 2);
+    }
+
+    fn described(description: &str, days_ago: i64, tags: &[&str]) -> Session {
+        Session {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..done_session(description, days_ago * 24 * 60)
+        }
+    }
+
+    fn suggested(app: &App) -> Vec<String> {
+        app.description_suggestions()
+            .iter()
+            .map(|s| s.description.clone())
+            .collect()
+    }
+
+    #[test]
+    fn description_suggestions_rank_frequent_and_recent_descriptions_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(
+            &dir,
+            vec![
+                described("rust review", 0, &[]),
+                described("old habit", 30, &[]),
+                described("old habit", 31, &[]),
+                described("old habit", 32, &[]),
+                described("writing", 1, &[]),
+                described("writing", 1, &[]),
+                described("writing", 2, &[]),
+            ],
+        );
+        app.creation_description.clear();
+        assert_eq!(suggested(&app), ["writing", "rust review", "old habit"]);
+
+        app.creation_description.set("rvw");
+        assert_eq!(suggested(&app), ["rust review"]);
+    }
+
+    #[test]
+    fn description_suggestions_offer_the_latest_session_and_skip_deleted_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut deleted = described("gone", 0, &[]);
+        deleted.delete();
+        let mut app = test_app(
+            &dir,
+            vec![
+                described("review", 5, &["old"]),
+                described("review", 1, &["new"]),
+                deleted,
+                described("", 0, &[]),
+            ],
+        );
+        app.creation_description.clear();
+        let suggestions = app.description_suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].tags, ["new"]);
+    }
+
+    #[test]
+    fn suggestions_only_complete_from_the_description_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, vec![described("review", 1, &[])]);
+        app.start_creation();
+        app.description_suggestion = Some(0);
+        app.mode = Mode::Creation(CreationField::Duration);
+        app.creation_duration.set("10m");
+
+        let enter = crossterm::event::KeyEvent::from(crossterm::event::KeyCode::Enter);
+        events::handle_creation_key(enter, CreationField::Duration, &mut app).unwrap();
+        assert_eq!(app.mode, Mode::Navigation);
+        assert_eq!(app.sessions[0].duration, Duration::from_secs(600));
+    }
+
+    fn on_disk(app: &App) -> Vec<Session> {
+        let mut sessions = app.session_service().load_sessions().unwrap();
+        sessions.sort_by_key(|s| Reverse(s.start));
+        sessions
+    }
+
+    #[test]
+    fn actions_target_the_marked_sessions_or_the_selected_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = vec![done_session("a", 10), done_session("b", 20), done_session("c", 30)];
+        let ids: Vec<String> = sessions.iter().map(|s| s.id.clone()).collect();
+        let mut app = test_app(&dir, sessions);
+        assert_eq!(app.target_ids(), HashSet::from([ids[0].clone()]));
+
+        app.toggle_mark();
+        assert_eq!(app.selected_session().unwrap().description, "b");
+        app.next();
+        app.toggle_mark();
+        assert_eq!(app.target_ids(), HashSet::from([ids[0].clone(), ids[2].clone()]));
+
+        app.toggle_mark_all();
+        assert_eq!(app.marked.len(), 3);
+        app.toggle_mark_all();
+        assert!(app.marked.is_empty());
+    }
+
+    #[test]
+    fn mark_all_only_marks_visible_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = vec![done_session("rust work", 10), done_session("email", 20)];
+        let mut app = test_app(&dir, sessions);
+        app.search_input.set("rust");
+        app.filter_sessions();
+        app.toggle_mark_all();
+        assert_eq!(app.target_sessions().len(), 1);
+        assert_eq!(app.target_sessions()[0].description, "rust work");
+    }
+
+    #[test]
+    fn bulk_delete_is_one_undo_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(
+            &dir,
+            vec![done_session("a", 10), done_session("b", 20), done_session("c", 30)],
+        );
+        app.toggle_mark();
+        app.toggle_mark();
+        app.delete_session().unwrap();
+        assert!(app.marked.is_empty());
+        let states: Vec<SessionState> = on_disk(&app).into_iter().map(|s| s.state).collect();
+        assert_eq!(states, [SessionState::Deleted, SessionState::Deleted, SessionState::Done]);
+
+        app.undo().unwrap();
+        assert!(on_disk(&app).iter().all(|s| s.state == SessionState::Done));
+        app.redo().unwrap();
+        assert_eq!(on_disk(&app).iter().filter(|s| s.state == SessionState::Deleted).count(), 2);
+    }
+
+    #[test]
+    fn actions_that_change_nothing_leave_no_undo_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let running = Session::new("running".to_string(), Duration::from_secs(1500), Utc::now());
+        let mut app = test_app(&dir, vec![done_session("done", 10), running]);
+        app.next();
+        assert_eq!(app.selected_session().unwrap().description, "done");
+        app.cancel_session().unwrap();
+        app.restore_session().unwrap();
+        assert!(app.history.undo().is_none());
+
+        // Of the marked sessions only the ones that change are part of the step
+        app.toggle_mark_all();
+        app.cancel_session().unwrap();
+        let undone = app.history.undo().unwrap();
+        assert_eq!(undone.len(), 1);
+        assert_eq!(undone[0].before.as_ref().unwrap().description, "running");
+    }
+
+    #[test]
+    fn tags_of_marked_sessions_are_added_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let tagged = |description: &str, minutes_ago| Session {
+            tags: vec!["old".to_string(), description.to_string()],
+            ..done_session(description, minutes_ago)
+        };
+        let mut app = test_app(&dir, vec![tagged("a", 10), tagged("b", 20), tagged("c", 30)]);
+        app.toggle_mark();
+        app.toggle_mark();
+        app.tags_input.set("rust, -old");
+        app.save_tags().unwrap();
+
+        let tags: Vec<Vec<String>> = on_disk(&app).into_iter().map(|s| s.tags).collect();
+        assert_eq!(tags, [vec!["a", "rust"], vec!["b", "rust"], vec!["old", "c"]]);
+    }
+
+    fn mouse(kind: crossterm::event::MouseEventKind, column: u16, row: u16) -> event::MouseEvent {
+        event::MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn scrolling_an_empty_list_does_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, Vec::new());
+        app.list_state.select(Some(0));
+        events::handle_mouse_event(mouse(MouseEventKind::ScrollDown, 5, 5), &mut app).unwrap();
+        events::handle_mouse_event(mouse(MouseEventKind::ScrollUp, 5, 5), &mut app).unwrap();
+        app.next();
+        app.previous();
+        assert!(app.selected_session().is_none());
+    }
+
+    #[test]
+    fn scrolling_wraps_around_the_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, vec![done_session("new", 10), done_session("old", 20)]);
+        events::handle_mouse_event(mouse(MouseEventKind::ScrollUp, 5, 5), &mut app).unwrap();
+        assert_eq!(app.selected_session().unwrap().description, "old");
+        events::handle_mouse_event(mouse(MouseEventKind::ScrollDown, 5, 5), &mut app).unwrap();
+        assert_eq!(app.selected_session().unwrap().description, "new");
+    }
+
+    #[test]
+    fn clicks_select_rows_focus_inputs_and_set_ratings() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app(&dir, vec![done_session("new", 10), done_session("old", 20)]);
+        app.areas.list = Rect::new(0, 3, 60, 20);
+        app.areas.search_input = Rect::new(0, 0, 60, 3);
+        app.areas.ratings = Rect::new(60, 3, 30, 6);
+        let click = MouseEventKind::Down(crossterm::event::MouseButton::Left);
+
+        // Border row first, then one row per session
+        events::handle_mouse_event(mouse(click, 5, 5), &mut app).unwrap();
+        assert_eq!(app.selected_session().unwrap().description, "old");
+        events::handle_mouse_event(mouse(click, 5, 15), &mut app).unwrap();
+        assert_eq!(app.selected_session().unwrap().description, "old");
+
+        // Third star of the motivation line
+        let star = 60 + 1 + info_pane::STARS_OFFSET + 2;
+        events::handle_mouse_event(mouse(click, star, 3 + 1 + 3), &mut app).unwrap();
+        assert_eq!(app.mode, Mode::Rating(RatingField::Motivation));
+        assert_eq!(app.rating_motivation, 3);
+
+        app.mode = Mode::Navigation;
+        events::handle_mouse_event(mouse(click, 5, 1), &mut app).unwrap();
+        assert_eq!(app.mode, Mode::Input(InputField::Search));
+    }
+
+    #[test]
+    fn zen_starts_the_next_pomodoro_like_the_last_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let last = Session {
+            tags: vec!["rust".to_string()],
+            project: "acme".to_string(),
+            ..done_session("deep work", 30)
+        };
+        let mut canceled = done_session("canceled", 10);
+        canceled.state = SessionState::Canceled;
+        let mut app = test_app(&dir, vec![canceled, last]);
+
+        app.start_next_session().unwrap();
+        let started = app.selected_session().unwrap();
+        assert_eq!(started.state, SessionState::Running);
+        assert_eq!(started.description, "deep work");
+        assert_eq!(started.tags, ["rust"]);
+        assert_eq!(started.project, "acme");
+    }
+
+    #[test]
+    fn every_fourth_pomodoro_is_followed_by_the_long_break() {
+        let dir = tempfile::tempdir().unwrap();
+        let midnight = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let sessions: Vec<Session> = (0..4)
+            .map(|i| Session {
+                start: midnight + chrono::Duration::minutes(i),
+                ..done_session("work", 0)
+            })
+            .collect();
+        let app = test_app(&dir, sessions.clone());
+        let pomodoro = &app.config.pomodoro_config;
+        assert_eq!(app.break_after(&sessions[2]), pomodoro.short_break);
+        assert_eq!(app.break_after(&sessions[3]), pomodoro.long_break);
     }
 }
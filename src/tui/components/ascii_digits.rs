@@ -30,6 +30,9 @@ pub fn get_digit(c: char) -> Vec<&'static str> {
         '9' => vec![
             " ##### ", "#     #", "#     #", " ######", "      #", "#     #", " ##### ",
         ],
+        '+' => vec![
+            "       ", "   #   ", "   #   ", " ##### ", "   #   ", "   #   ", "       ",
+        ],
         ':' => vec![
             "       ", "   #   ", "   #   ", "       ", "   #   ", "   #   ", "       ",
         ],
@@ -1,9 +1,10 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders},
     Frame,
 };
 use crate::tui::app::{App, InputField, Mode};
+use crate::tui::components::text_field;
 
 pub fn render(f: &mut Frame, area: Rect, app: &App) {
     let top_chunks = Layout::default()
@@ -25,35 +26,50 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
     } else {
         "Date"
     };
-    let date_input = Paragraph::new(app.date_input.as_str())
-        .block(Block::default().borders(Borders::ALL).title(date_title));
+    let date_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(app.mode == Mode::Input(InputField::Date)))
+        .title(date_title);
+    let date_input = text_field::render(&app.date_input, date_chunk, date_block);
     f.render_widget(date_input, date_chunk);
 
-    let search_title = if let Mode::Input(InputField::Search) = app.mode {
-        "Search (Active)"
+    let search_title = match (&app.mode, &app.query_error) {
+        (_, Some(error)) => format!("Search ({})", error),
+        (Mode::Input(InputField::Search), None) => "Search (Active)".to_string(),
+        _ => "Search (/)".to_string(),
+    };
+    let search_border = if app.query_error.is_some() {
+        app.theme.error
     } else {
-        "Search (/)"
+        app.theme.border(app.mode == Mode::Input(InputField::Search))
     };
-    let search_input = Paragraph::new(app.search_input.as_str())
-        .block(Block::default().borders(Borders::ALL).title(search_title));
+    let search_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(search_border)
+        .title(search_title);
+    let search_input = text_field::render(&app.search_input, search_chunk, search_block);
     f.render_widget(search_input, search_chunk);
 }
 
-pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
+/// Areas of the date and the search input.
+pub fn input_areas(area: Rect) -> (Rect, Rect) {
     let top_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(area);
+    (top_chunks[0], top_chunks[1])
+}
+
+pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
+    let (date_area, search_area) = input_areas(area);
 
     match app.mode {
-        Mode::Input(InputField::Date) => Some((
-            top_chunks[0].x + app.date_input.len() as u16 + 1,
-            top_chunks[0].y + 1,
-        )),
-        Mode::Input(InputField::Search) => Some((
-            top_chunks[1].x + app.search_input.len() as u16 + 1,
-            top_chunks[1].y + 1,
-        )),
+        Mode::Input(InputField::Date) => {
+            Some(text_field::cursor_position(&app.date_input, date_area))
+        }
+        Mode::Input(InputField::Search) => {
+            Some(text_field::cursor_position(&app.search_input, search_area))
+        }
         _ => None,
     }
 }
@@ -1,28 +1,20 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::Modifier,
+    text::Line,
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 use crate::tui::app::{App, Mode, RatingField};
-use crate::tui::components::ratings;
+use crate::tui::components::{ratings, text_field};
 
 pub fn render(f: &mut Frame, area: Rect, app: &App) {
-    let right_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Length(6), // Increased for extra rating
-                Constraint::Percentage(40), 
-                Constraint::Min(5),    
-            ]
-            .as_ref()
-        )
-        .split(area);
-    
+    let right_chunks = pane_chunks(area);
+
     let rating_chunk = right_chunks[0];
-    let tags_chunk = right_chunks[1];
-    let notes_chunk = right_chunks[2];
+    let project_chunk = right_chunks[1];
+    let tags_chunk = right_chunks[2];
+    let notes_chunk = right_chunks[3];
 
     // --- Ratings Pane ---
     let (ratings_mental, ratings_physical, ratings_cognitive, ratings_motivation) = if let Mode::Rating(_) = app.mode {
@@ -40,7 +32,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
     };
 
     let ratings_title = if let Mode::Rating(_) = app.mode { "Ratings (Active)" } else { "Ratings" };
-    let active_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let active_style = app.theme.highlight.add_modifier(Modifier::BOLD);
     
     let format_rating_line = |label: &str, val: u8, is_active: bool| {
         let stars = ratings::render_stars(val);
@@ -65,74 +57,120 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
         lines.push(ratatui::text::Line::from(format_rating_line("Motivation", ratings_motivation, false)));
     }
 
-    let ratings_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title(ratings_title));
+    let ratings_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(matches!(app.mode, Mode::Rating(_))))
+        .title(ratings_title);
+    let ratings_widget = Paragraph::new(lines).block(ratings_block);
     f.render_widget(ratings_widget, rating_chunk);
 
-    // --- Tags Pane ---
-    let tags_title = if app.mode == Mode::Tagging {
-        "Tags (Active)"
+    // --- Project Pane ---
+    let project_title = if app.mode == Mode::Project {
+        "Project (Active)"
     } else {
-        "Tags"
+        "Project"
     };
-    let tags_text = if app.mode == Mode::Tagging {
-        app.tags_input.clone()
+    let project_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(app.mode == Mode::Project))
+        .title(project_title);
+    let project_widget = if app.mode == Mode::Project {
+        text_field::render(&app.project_input, project_chunk, project_block)
     } else {
-        if let Some(idx) = app.list_state.selected() {
-            if let Some(session) = app.filtered_sessions.get(idx) {
-                session.tags.join(", ")
+        let project = app.selected_session().map(|s| s.project.clone()).unwrap_or_default();
+        Paragraph::new(project).block(project_block)
+    };
+    f.render_widget(project_widget, project_chunk);
+
+    // --- Tags Pane ---
+    let tags_title = if app.mode == Mode::Tagging && !app.marked.is_empty() {
+        format!("Tags for {} marked (tag: add, -tag: remove)", app.marked.len())
+    } else if app.mode == Mode::Tagging {
+        "Tags (Active)".to_string()
+    } else {
+        "Tags".to_string()
+    };
+    let tags_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(app.mode == Mode::Tagging))
+        .title(tags_title);
+    let tags_widget = if app.mode == Mode::Tagging {
+        let inner = tags_chunk.inner(Margin::new(1, 1));
+        let (input_line, _) = app.tags_input.view(inner.width, 1);
+        let mut lines: Vec<Line> = input_line.into_iter().map(Line::from).collect();
+        let suggestions = app.tag_suggestions();
+        if !suggestions.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Tab: complete, ↑/↓: choose"));
+        }
+        for (i, tag) in suggestions.into_iter().enumerate() {
+            if i == app.tag_suggestion {
+                lines.push(Line::styled(format!("> {}", tag), app.theme.highlight));
             } else {
-                String::new()
+                lines.push(Line::from(format!("  {}", tag)));
             }
-        } else {
-            String::new()
         }
+        Paragraph::new(lines).block(tags_block)
+    } else {
+        let tags = app.selected_session().map(|s| s.tags.join(", ")).unwrap_or_default();
+        Paragraph::new(tags)
+            .block(tags_block)
+            .wrap(ratatui::widgets::Wrap { trim: true })
     };
-    let tags_widget = Paragraph::new(tags_text)
-        .block(Block::default().borders(Borders::ALL).title(tags_title))
-        .wrap(ratatui::widgets::Wrap { trim: true });
     f.render_widget(tags_widget, tags_chunk);
 
     // --- Notes Pane ---
     let notes_title = if app.mode == Mode::Notes {
-        "Notes (Active)"
+        "Notes (Active, Enter: new line, Ctrl-S/Alt-Enter: save, Esc: cancel)"
     } else {
         "Notes"
     };
-    let notes_text = if app.mode == Mode::Notes {
-        app.notes_input.clone()
+    let notes_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(app.mode == Mode::Notes))
+        .title(notes_title);
+    let notes_widget = if app.mode == Mode::Notes {
+        text_field::render(&app.notes_input, notes_chunk, notes_block)
     } else {
-        if let Some(idx) = app.list_state.selected() {
-            if let Some(session) = app.filtered_sessions.get(idx) {
-                session.notes.clone()
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        }
+        let notes = app.selected_session().map(|s| s.notes.clone()).unwrap_or_default();
+        Paragraph::new(notes)
+            .block(notes_block)
+            .wrap(ratatui::widgets::Wrap { trim: false })
     };
-    let notes_widget = Paragraph::new(notes_text)
-        .block(Block::default().borders(Borders::ALL).title(notes_title))
-        .wrap(ratatui::widgets::Wrap { trim: true });
     f.render_widget(notes_widget, notes_chunk);
 }
 
-pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
-    let right_chunks = Layout::default()
+/// Area of the ratings pane, clicking a star sets that rating.
+pub fn rating_area(area: Rect) -> Rect {
+    pane_chunks(area)[0]
+}
+
+/// Column of the first star inside the ratings pane, after the padded label and `[`.
+pub const STARS_OFFSET: u16 = 18;
+
+/// Ratings, project, tags and notes from top to bottom, shared by drawing, clicks and the cursor.
+fn pane_chunks(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(6), Constraint::Percentage(40), Constraint::Min(5)].as_ref())
-        .split(area);
-    
+        .constraints(
+            [
+                Constraint::Length(6), // Increased for extra rating
+                Constraint::Length(3),
+                Constraint::Percentage(40),
+                Constraint::Min(5),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
+
+pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
+    let right_chunks = pane_chunks(area);
+
     match app.mode {
-        Mode::Tagging => Some((
-            right_chunks[1].x + app.tags_input.len() as u16 + 1,
-            right_chunks[1].y + 1,
-        )),
-        Mode::Notes => Some((
-            right_chunks[2].x + app.notes_input.len() as u16 + 1,
-            right_chunks[2].y + 1,
-        )),
+        Mode::Project => Some(text_field::cursor_position(&app.project_input, right_chunks[1])),
+        Mode::Tagging => Some(text_field::cursor_position(&app.tags_input, right_chunks[2])),
+        Mode::Notes => Some(text_field::cursor_position(&app.notes_input, right_chunks[3])),
         _ => None,
     }
 }
\ No newline at end of file
@@ -1,21 +1,80 @@
+use crate::config::{Preset, View};
+use crate::tui::components::text_field;
+use crate::tui::keymap::Keymap;
+use crate::tui::text_input::TextInput;
+use crate::tui::theme::Theme;
 use ratatui::{
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
 };
 
-const KEYBINDS_TEXT: &str =
-    "j/k: up/down | /: search | i: date filter | t: tags | n: notes | s: duplicated & start selected | r: rate | a: create | e: edit | c: cancel | x: delete | f: fast filter | z: zen | q: quit | Esc: back";
+const FAST_FILTER_TEXT: &str = "t: Today | w: Last Week | c: Clear Filter | s: Save as View | Esc: Cancel";
 
-const FAST_FILTER_TEXT: &str = "t: Today | w: Last Week | c: Clear Filter | Esc: Cancel";
-
-pub fn render_keybinds() -> Paragraph<'static> {
-    Paragraph::new(KEYBINDS_TEXT)
-        .style(Style::default().fg(Color::Yellow))
+pub fn render_keybinds(keymap: &Keymap, presets: &[Preset], theme: &Theme) -> Paragraph<'static> {
+    let mut text = keymap.bar_text();
+    for (i, preset) in presets.iter().take(9).enumerate() {
+        text.push_str(&format!(" | {}: {}", i + 1, preset.name));
+    }
+    Paragraph::new(text)
+        .style(theme.highlight)
         .block(Block::default().borders(Borders::ALL).title("Keybinds"))
 }
 
-pub fn render_fast_filter() -> Paragraph<'static> {
-    Paragraph::new(FAST_FILTER_TEXT)
-        .style(Style::default().fg(Color::Cyan))
+pub fn render_fast_filter(views: &[View], theme: &Theme) -> Paragraph<'static> {
+    let mut text = FAST_FILTER_TEXT.to_string();
+    for (i, view) in views.iter().take(9).enumerate() {
+        text.push_str(&format!(" | {}: {}", i + 1, view.name));
+    }
+    Paragraph::new(text)
+        .style(theme.accent)
         .block(Block::default().borders(Borders::ALL).title("Fast Filter"))
 }
+
+pub fn render_save_view(name: &TextInput, area: Rect, theme: &Theme) -> Paragraph<'static> {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border(true))
+        .title("Save View As (Enter: save, Esc: cancel)");
+    text_field::render(name, area, block)
+}
+
+/// Centered overlay listing every binding per mode, scrolled with the up/down keys.
+pub fn render_help(f: &mut Frame, keymap: &Keymap, theme: &Theme, scroll: u16) {
+    let area = f.area();
+    let width = area.width.saturating_sub(4).min(70);
+    let height = area.height.saturating_sub(4);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let mut lines = Vec::new();
+    for (mode, bindings) in keymap.help_sections() {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            mode,
+            theme.accent.add_modifier(Modifier::BOLD),
+        )));
+        for (keys, description) in bindings {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<14}", keys), theme.highlight),
+                Span::raw(description),
+            ]));
+        }
+    }
+
+    let help = Paragraph::new(lines).scroll((scroll, 0)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Help (up/down: scroll, any other key: close)"),
+    );
+    f.render_widget(Clear, popup);
+    f.render_widget(help, popup);
+}
@@ -5,4 +5,5 @@ pub mod ascii_digits;
 pub mod filter_bar;
 pub mod session_list;
 pub mod info_pane;
-pub mod overlay_bar;
\ No newline at end of file
+pub mod overlay_bar;
+pub mod text_field;
\ No newline at end of file
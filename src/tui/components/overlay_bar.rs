@@ -1,59 +1,155 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph},
+    style::Modifier,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
+use crate::date_time::format_duration_human_readable;
 use crate::tui::app::{App, Mode, CreationField};
+use crate::tui::components::text_field;
 
 pub fn render(f: &mut Frame, area: Rect, app: &App) {
     if let Mode::Creation(ref field) = app.mode {
-         let creation_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage(70),
-                    Constraint::Percentage(30),
-                ]
-                .as_ref(),
-            )
-            .split(area);
-
-         let desc_title = if let CreationField::Description = field { "Description (Active)" } else { "Description" };
-         let duration_title = if let CreationField::Duration = field { "Duration (min) (Active)" } else { "Duration (min)" };
+         let creation_chunks = creation_chunks(area, app.creation_log);
+
+         let desc_title = match (field, app.creation_log) {
+             (CreationField::Description, true) => "Log past session: Description (Active)",
+             (CreationField::Description, false) => "Description (Active)",
+             (_, true) => "Log past session: Description",
+             (_, false) => "Description",
+         };
+         let duration_title = match (field, app.creation_log) {
+             (CreationField::Duration, true) => "Duration (Active)",
+             (CreationField::Duration, false) => "Duration, 0: stopwatch (Active)",
+             _ => "Duration",
+         };
+         let preset = app.creation_preset.and_then(|i| app.config.presets.get(i));
+         let desc_title = match preset {
+             Some(preset) => format!("{} [preset: {}, C-n/C-p: presets]", desc_title, preset.name),
+             None if !app.config.presets.is_empty() => format!("{} [C-n/C-p: presets]", desc_title),
+             None => desc_title.to_string(),
+         };
          
-         let desc_input = Paragraph::new(app.creation_description.as_str())
-            .block(Block::default().borders(Borders::ALL).title(desc_title));
+         let desc_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(app.theme.border(*field == CreationField::Description))
+            .title(desc_title);
+         let desc_input =
+            text_field::render(&app.creation_description, creation_chunks[0], desc_block);
          f.render_widget(desc_input, creation_chunks[0]);
 
-         let duration_input = Paragraph::new(app.creation_duration.as_str())
-            .block(Block::default().borders(Borders::ALL).title(duration_title));
+         let duration_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(app.theme.border(*field == CreationField::Duration))
+            .title(duration_title);
+         let duration_input =
+            text_field::render(&app.creation_duration, creation_chunks[1], duration_block);
          f.render_widget(duration_input, creation_chunks[1]);
+
+         if app.creation_log {
+             let start_title = if let CreationField::Start = field {
+                 "Start, e.g. 14:00 (Active)"
+             } else {
+                 "Start"
+             };
+             let start_block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.border(*field == CreationField::Start))
+                .title(start_title);
+             let start_input =
+                text_field::render(&app.creation_start, creation_chunks[2], start_block);
+             f.render_widget(start_input, creation_chunks[2]);
+         }
     } else if app.mode == Mode::DeleteConfirm {
-        let confirm_text = "Are you sure you want to delete this session? (y/n)";
+        let confirm_text = match (app.show_trash, app.marked.len()) {
+            (true, 0) => "Permanently remove this session from disk? (y/n)".to_string(),
+            (true, n) => format!("Permanently remove {} marked sessions from disk? (y/n)", n),
+            (false, 0) => "Are you sure you want to delete this session? (y/n)".to_string(),
+            (false, n) => format!("Are you sure you want to delete {} marked sessions? (y/n)", n),
+        };
         let confirm_paragraph = Paragraph::new(confirm_text)
-            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            .style(app.theme.error.add_modifier(Modifier::BOLD))
             .block(Block::default().borders(Borders::ALL).title("Delete Confirmation"))
             .alignment(ratatui::layout::Alignment::Center);
         f.render_widget(confirm_paragraph, area);
     }
 }
 
-pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
-    let creation_chunks = Layout::default()
+/// Dropdown of past descriptions below the description input, drawn over the list.
+pub fn render_description_suggestions(f: &mut Frame, area: Rect, app: &App) {
+    if app.mode != Mode::Creation(CreationField::Description) {
+        return;
+    }
+    let suggestions = app.description_suggestions();
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let description_area = creation_chunks(area, app.creation_log)[0];
+    let height = (suggestions.len() as u16 + 2)
+        .min(f.area().bottom().saturating_sub(description_area.bottom()));
+    let popup = Rect::new(
+        description_area.x,
+        description_area.bottom(),
+        description_area.width,
+        height,
+    );
+
+    let lines: Vec<Line> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, session)| {
+            let mut details = vec![format_duration_human_readable(session.duration)];
+            details.extend(session.tags.iter().cloned());
+            let text = format!("{} ({})", session.description, details.join(", "));
+            if app.description_suggestion == Some(i) {
+                Line::styled(format!("> {}", text), app.theme.highlight)
+            } else {
+                Line::from(format!("  {}", text))
+            }
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Past descriptions (↑/↓: choose, Enter: use)");
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// Description and duration inputs, the log form also has a start input.
+fn creation_chunks(area: Rect, log: bool) -> std::rc::Rc<[Rect]> {
+    let constraints = if log {
+        vec![
+            Constraint::Percentage(50),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+        ]
+    } else {
+        vec![Constraint::Percentage(70), Constraint::Percentage(30)]
+    };
+    Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
-        .split(area);
+        .constraints(constraints)
+        .split(area)
+}
+
+pub fn get_cursor_position(area: Rect, app: &App) -> Option<(u16, u16)> {
+    let creation_chunks = creation_chunks(area, app.creation_log);
 
     match app.mode {
-        Mode::Creation(CreationField::Description) => Some((
-            creation_chunks[0].x + app.creation_description.len() as u16 + 1,
-            creation_chunks[0].y + 1,
+        Mode::Creation(CreationField::Description) => Some(text_field::cursor_position(
+            &app.creation_description,
+            creation_chunks[0],
         )),
-        Mode::Creation(CreationField::Duration) => Some((
-            creation_chunks[1].x + app.creation_duration.len() as u16 + 1,
-            creation_chunks[1].y + 1,
+        Mode::Creation(CreationField::Duration) => Some(text_field::cursor_position(
+            &app.creation_duration,
+            creation_chunks[1],
         )),
+        Mode::Creation(CreationField::Start) if app.creation_log => Some(
+            text_field::cursor_position(&app.creation_start, creation_chunks[2]),
+        ),
         _ => None,
     }
 }
@@ -1,8 +1,9 @@
-use crate::session::SessionState;
+use crate::session::{EndReason, SessionState};
 use crate::tui::app::App;
+use crate::tui::grouping::GroupBy;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::Modifier,
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
 };
@@ -16,56 +17,155 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
 
     let list_items_chunk = list_area_chunks[0];
     let summary_chunk = list_area_chunks[1];
+    app.areas.list = list_items_chunk;
 
     let list_width = list_items_chunk.width.saturating_sub(5) as usize;
-    let items: Vec<ListItem> = app
-        .filtered_sessions
+    let rows = list_rows(app);
+    let items: Vec<ListItem> = rows
         .iter()
-        .map(|s| {
-            let base_text = s.to_string();
-            let status_text = match s.state {
-                SessionState::Running => {
-                    let remaining = s.remaining_duration();
-                    if remaining.as_secs() == 0 {
-                        "[Done]".to_string()
-                    } else {
-                        let mins = remaining.as_secs() / 60;
-                        let secs = remaining.as_secs() % 60;
-                        format!("[Running: {:02}:{:02}]", mins, secs)
+        .map(|row| match row {
+            ListRow::Header(header) => ListItem::new(header.clone())
+                .style(app.theme.accent.add_modifier(Modifier::BOLD)),
+            ListRow::Session(idx) => {
+                let s = &app.filtered_sessions[*idx];
+                let base_text = s.to_string();
+                let status_text = match s.state {
+                    SessionState::Running if s.is_stopwatch() => {
+                        format!("[Stopwatch: {}]", format_clock(s.elapsed_duration()))
                     }
-                }
-                SessionState::Done => "[Done]".to_string(),
-                SessionState::Canceled => "[Canceled]".to_string(),
-                SessionState::Deleted => "[Deleted]".to_string(),
-            };
+                    SessionState::Running if !s.overtime_duration().is_zero() => {
+                        format!("[Overtime: +{}]", format_clock(s.overtime_duration()))
+                    }
+                    SessionState::Running => {
+                        let remaining = s.remaining_duration();
+                        if remaining.as_secs() == 0 {
+                            "[Done]".to_string()
+                        } else {
+                            let mins = remaining.as_secs() / 60;
+                            let secs = remaining.as_secs() % 60;
+                            format!("[Running: {:02}:{:02}]", mins, secs)
+                        }
+                    }
+                    SessionState::Planned => match s.repeat {
+                        Some(repeat) => {
+                            format!("[Planned {}, {}]", s.start.format("%H:%M"), repeat.label())
+                        }
+                        None => format!("[Planned {}]", s.start.format("%H:%M")),
+                    },
+                    SessionState::Done if s.end_reason == Some(EndReason::StoppedEarly) => {
+                        "[Done early]".to_string()
+                    }
+                    SessionState::Done => "[Done]".to_string(),
+                    SessionState::Canceled if s.end_reason == Some(EndReason::Interrupted) => {
+                        "[Interrupted]".to_string()
+                    }
+                    SessionState::Canceled => "[Canceled]".to_string(),
+                    SessionState::Deleted => "[Deleted]".to_string(),
+                };
+
+                let mark = if app.marked.contains(&s.id) { "* " } else { "  " };
 
-            let content_len = base_text.chars().count() + status_text.chars().count();
-            let padding_len = list_width.saturating_sub(content_len);
-            let padding = " ".repeat(padding_len);
+                let content_len =
+                    mark.len() + base_text.chars().count() + status_text.chars().count();
+                let padding_len = list_width.saturating_sub(content_len);
+                let padding = " ".repeat(padding_len);
 
-            ListItem::new(format!("{}{}{}", base_text, padding, status_text))
+                let item =
+                    ListItem::new(format!("{}{}{}{}", mark, base_text, padding, status_text));
+                if s.overtime_duration().is_zero() || s.is_stopwatch() {
+                    item
+                } else {
+                    item.style(app.theme.error)
+                }
+            }
         })
         .collect();
 
+    let selected_row = app
+        .list_state
+        .selected()
+        .and_then(|idx| rows.iter().position(|row| *row == ListRow::Session(idx)));
+    app.display_list_state.select(selected_row);
+
+    let order = if app.sort_reversed { " (reversed)" } else { "" };
+    let title = if app.show_trash {
+        "Trash (T: back, R: restore, x: purge)".to_string()
+    } else {
+        format!(
+            "Sessions (sort: {}{}, group: {})",
+            app.sort_key.label(),
+            order,
+            app.group_by.label()
+        )
+    };
     let list = List::new(items)
-        .block(Block::default().title("Sessions").borders(Borders::ALL))
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selection)
         .highlight_symbol("> ");
 
-    f.render_stateful_widget(list, list_items_chunk, &mut app.list_state);
+    f.render_stateful_widget(list, list_items_chunk, &mut app.display_list_state);
 
     // --- Summary Bar ---
     let total_count = app.filtered_sessions.len();
-    let total_duration: Duration = app.filtered_sessions.iter().map(|s| s.duration).sum();
+    let total_duration: Duration =
+        app.filtered_sessions.iter().map(|s| s.actual_duration()).sum();
     let total_mins = total_duration.as_secs() / 60;
     let total_hours = total_mins / 60;
     let remaining_mins = total_mins % 60;
 
-    let summary_text = format!(
+    let mut summary_text = format!(
         "Count: {} | Total Duration: {:02}:{:02}",
         total_count, total_hours, remaining_mins
     );
+    if !app.marked.is_empty() {
+        summary_text.push_str(&format!(" | Marked: {}", app.marked.len()));
+    }
+    if let Some(message) = &app.status_message {
+        summary_text.push_str(&format!(" | {}", message));
+    }
 
-    let summary_paragraph = Paragraph::new(summary_text).style(Style::default().fg(Color::Cyan));
+    let summary_paragraph = Paragraph::new(summary_text).style(app.theme.accent);
     f.render_widget(summary_paragraph, summary_chunk);
 }
+
+#[derive(Debug, PartialEq)]
+pub enum ListRow {
+    Header(String),
+    /// Index into `App::filtered_sessions`
+    Session(usize),
+}
+
+/// Rows of the rendered list, sessions with a header and subtotal before each group.
+pub fn list_rows(app: &App) -> Vec<ListRow> {
+    let mut rows = Vec::new();
+    let mut current_group: Option<String> = None;
+
+    for (idx, session) in app.filtered_sessions.iter().enumerate() {
+        if app.group_by != GroupBy::None {
+            let group = app.group_by.group_name(session);
+            if current_group.as_ref() != Some(&group) {
+                let members: Vec<_> = app.filtered_sessions[idx..]
+                    .iter()
+                    .take_while(|s| app.group_by.group_name(s) == group)
+                    .collect();
+                let total: Duration = members.iter().map(|s| s.actual_duration()).sum();
+                let total_mins = total.as_secs() / 60;
+                rows.push(ListRow::Header(format!(
+                    "{} | {} sessions | {:02}:{:02}",
+                    group,
+                    members.len(),
+                    total_mins / 60,
+                    total_mins % 60
+                )));
+                current_group = Some(group);
+            }
+        }
+        rows.push(ListRow::Session(idx));
+    }
+    rows
+}
+
+fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,25 @@
+use ratatui::{
+    layout::{Margin, Rect},
+    text::{Line, Text},
+    widgets::{Block, Paragraph},
+};
+
+use crate::tui::text_input::TextInput;
+
+fn inner(area: Rect) -> Rect {
+    area.inner(Margin::new(1, 1))
+}
+
+/// The visible part of an input inside a bordered block covering `area`.
+pub fn render<'a>(input: &TextInput, area: Rect, block: Block<'a>) -> Paragraph<'a> {
+    let inner = inner(area);
+    let (lines, _) = input.view(inner.width, inner.height);
+    Paragraph::new(Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>())).block(block)
+}
+
+/// Terminal position of the cursor of an input rendered with `render`.
+pub fn cursor_position(input: &TextInput, area: Rect) -> (u16, u16) {
+    let inner = inner(area);
+    let (_, (x, y)) = input.view(inner.width, inner.height);
+    (inner.x + x, inner.y + y)
+}
@@ -1,68 +1,200 @@
+use chrono::Utc;
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    widgets::Paragraph,
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{LineGauge, Paragraph},
     Frame,
 };
 
-use crate::session::Session;
+use crate::date_time::{add_saturating, format_duration_human_readable};
+use crate::session::{cycle, SessionState};
+use crate::tui::app::App;
 use crate::tui::components::ascii_digits;
+use crate::tui::keymap::Action;
 
-pub fn render(f: &mut Frame, session: Option<&Session>) {
-    let area = f.area();
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage(30),
-                Constraint::Percentage(40),
-                Constraint::Percentage(30),
-            ]
-            .as_ref(),
-        )
-        .split(area);
-
-    if let Some(s) = session {
-        let remaining = s.remaining_duration();
-        let mins = remaining.as_secs() / 60;
-        let secs = remaining.as_secs() % 60;
-        let time_str = format!("{:02}:{:02}", mins, secs);
-
-        let big_text_lines = to_big_text(&time_str);
-
-        let mut lines = Vec::new();
-
-        // Description (Above)
-        lines.push(ratatui::text::Line::from(ratatui::text::Span::styled(
-            s.description.clone(),
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )));
-
-        // Spacing
-        lines.push(ratatui::text::Line::from(""));
-        lines.push(ratatui::text::Line::from(""));
-
-        // Time (Below)
-        for l in big_text_lines {
-            lines.push(ratatui::text::Line::from(ratatui::text::Span::styled(
-                l,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )));
+/// Full screen focus view: the running session's countdown with its progress and place in
+/// the cycle, or the break after the last pomodoro and what Enter starts next.
+pub fn render(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let rows = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(7),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .flex(Flex::Center)
+    .split(f.area());
+    let (title_row, cycle_row, digits_row, gauge_row, next_row) =
+        (rows[0], rows[1], rows[3], rows[5], rows[7]);
+
+    let now = Utc::now();
+    let pomodoro = &app.config.pomodoro_config;
+    let today = cycle::pomodoros_on(&app.sessions, now.date_naive());
+    let today_text = match pomodoro.daily_goal {
+        Some(goal) => format!("Today {}/{}", today, goal),
+        None => format!("Today {}", today),
+    };
+
+    let running = app
+        .sessions
+        .iter()
+        .find(|s| s.state == SessionState::Running);
+    if let Some(s) = running {
+        let position = cycle::cycle_position(&app.sessions, s, pomodoro.cycle_length);
+        let overtime = s.overtime_duration();
+        let in_overtime = !overtime.is_zero() && !s.is_stopwatch();
+        let elapsed = s.elapsed_duration().min(s.duration);
+
+        centered_line(
+            f,
+            title_row,
+            Span::styled(
+                s.description.clone(),
+                theme.accent.add_modifier(Modifier::BOLD),
+            ),
+        );
+        centered_line(
+            f,
+            cycle_row,
+            Span::raw(format!(
+                "Pomodoro {}/{} · {}",
+                position.in_cycle, position.cycle_length, today_text
+            )),
+        );
+        // Overtime counts up from zero with a plus sign in the error color
+        let (clock, clock_style) = if s.is_stopwatch() {
+            (
+                format_clock(s.elapsed_duration().as_secs()),
+                theme.highlight,
+            )
+        } else if in_overtime {
+            (
+                format!("+{}", format_clock(overtime.as_secs())),
+                theme.error,
+            )
+        } else {
+            (
+                format_clock(s.remaining_duration().as_secs()),
+                theme.highlight,
+            )
+        };
+        render_digits(f, digits_row, &clock, clock_style);
+
+        if !s.is_stopwatch() {
+            let ratio = elapsed.as_secs_f64() / s.duration.as_secs_f64();
+            let label = if in_overtime {
+                format!(
+                    "{} / {} overtime",
+                    format_clock(elapsed.as_secs()),
+                    format_clock(s.duration.as_secs())
+                )
+            } else {
+                format!(
+                    "{} / {}",
+                    format_clock(elapsed.as_secs()),
+                    format_clock(s.duration.as_secs())
+                )
+            };
+            let gauge = LineGauge::default()
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(label)
+                .filled_symbol(symbols::line::THICK_HORIZONTAL)
+                .filled_style(if in_overtime {
+                    theme.error
+                } else {
+                    theme.highlight
+                });
+            f.render_widget(gauge, centered(gauge_row, 60));
         }
 
-        let p = Paragraph::new(lines).alignment(ratatui::layout::Alignment::Center);
+        let next_break = app.break_after(s);
+        let kind = if position.is_last_in_cycle() {
+            "Long break"
+        } else {
+            "Break"
+        };
+        let when = if s.overtime {
+            format!("after finishing with {}", app.keymap.label(Action::Finish))
+        } else {
+            format!("at {}", s.planned_end().format("%H:%M"))
+        };
+        centered_line(
+            f,
+            next_row,
+            Span::raw(format!(
+                "{} {} {}",
+                kind,
+                format_duration_human_readable(next_break),
+                when
+            )),
+        );
+        return;
+    }
 
-        f.render_widget(p, vertical[1]);
-    } else {
-        let p = Paragraph::new("No active session")
-            .alignment(ratatui::layout::Alignment::Center)
-            .style(Style::default().fg(Color::Red));
-        f.render_widget(p, vertical[1]);
+    let Some(last) = app.last_pomodoro() else {
+        centered_line(f, title_row, Span::styled("No active session", theme.error));
+        centered_line(f, cycle_row, Span::raw(today_text));
+        return;
     };
+
+    let last_end = last.ended_at.unwrap_or(last.planned_end());
+    let break_end = add_saturating(last_end, app.break_after(last));
+    if now < break_end {
+        centered_line(
+            f,
+            title_row,
+            Span::styled("Break", theme.accent.add_modifier(Modifier::BOLD)),
+        );
+        let left = (break_end - now).num_seconds().max(0) as u64;
+        render_digits(f, digits_row, &format_clock(left), theme.highlight);
+    } else {
+        centered_line(f, title_row, Span::styled("No active session", theme.error));
+    }
+    centered_line(f, cycle_row, Span::raw(today_text));
+    centered_line(
+        f,
+        next_row,
+        Span::raw(format!(
+            "Enter: start {} ({})",
+            last.description,
+            format_duration_human_readable(last.duration)
+        )),
+    );
+}
+
+fn centered_line(f: &mut Frame, area: Rect, span: Span) {
+    f.render_widget(
+        Paragraph::new(Line::from(span)).alignment(Alignment::Center),
+        area,
+    );
+}
+
+fn centered(area: Rect, width: u16) -> Rect {
+    let width = width.min(area.width);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y,
+        width,
+        area.height,
+    )
+}
+
+fn render_digits(f: &mut Frame, area: Rect, time: &str, style: Style) {
+    let lines: Vec<Line> = to_big_text(time)
+        .into_iter()
+        .map(|l| Line::styled(l, style.add_modifier(Modifier::BOLD)))
+        .collect();
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
+
+fn format_clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
 }
 
 fn to_big_text(s: &str) -> Vec<String> {
@@ -73,9 +205,62 @@ fn to_big_text(s: &str) -> Vec<String> {
 
         for i in 0..7 {
             lines[i].push_str(art[i]);
-            lines[i].push_str(" ");
+            lines[i].push(' ');
         }
     }
     lines
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Session;
+    use crate::tui::app::tests::{done_session, test_app};
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::time::Duration;
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// A done pomodoro from the start of today, so it never falls on yesterday
+    fn earlier_today() -> Session {
+        let midnight = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        Session {
+            start: midnight,
+            ..done_session("earlier", 0)
+        }
+    }
+
+    #[test]
+    fn shows_cycle_goal_and_the_break_after_the_running_pomodoro() {
+        let dir = tempfile::tempdir().unwrap();
+        let running = Session::new("focus".to_string(), Duration::from_secs(25 * 60), Utc::now());
+        let mut app = test_app(&dir, vec![earlier_today(), running]);
+        app.config.pomodoro_config.daily_goal = Some(8);
+
+        let screen = screen(&app);
+        assert!(screen.contains("focus"));
+        assert!(screen.contains("Pomodoro 2/4 · Today 2/8"));
+        assert!(screen.contains("Break 5m at"));
+    }
+
+    #[test]
+    fn offers_the_next_pomodoro_when_nothing_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = test_app(&dir, vec![earlier_today()]);
+
+        let screen = screen(&app);
+        assert!(screen.contains("No active session"));
+        assert!(screen.contains("Today 1"));
+        assert!(screen.contains("Enter: start earlier (1m)"));
+    }
+}
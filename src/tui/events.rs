@@ -1,112 +1,120 @@
 use chrono::Utc;
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Position, Rect},
+    Terminal,
+};
 use std::error::Error;
 use std::io::Stdout;
 
 use super::app::{App, CreationField, InputField, Mode, RatingField};
+use super::components::info_pane;
+use super::components::session_list::{list_rows, ListRow};
+use super::keymap::Action;
+use crate::session::SessionState;
 
 pub fn handle_key_event(
     key: KeyEvent,
     app: &mut App,
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
 ) -> Result<bool, Box<dyn Error>> {
+    app.status_message = None;
+
     match &app.mode {
-        Mode::Navigation => match key.code {
-            KeyCode::Char('q') => return Ok(false),
-            KeyCode::Char('i') => app.mode = Mode::Input(InputField::Date),
-            KeyCode::Char('/') => app.mode = Mode::Input(InputField::Search),
-            KeyCode::Char('j') => app.next(),
-            KeyCode::Char('k') => app.previous(),
-            KeyCode::Char('t') => {
+        Mode::Navigation => match app.keymap.action(&key) {
+            Some(Action::Quit) => return Ok(false),
+            Some(Action::DateFilter) => app.mode = Mode::Input(InputField::Date),
+            Some(Action::Search) => app.mode = Mode::Input(InputField::Search),
+            Some(Action::Down) => app.next(),
+            Some(Action::Up) => app.previous(),
+            Some(Action::Tags) if !app.marked.is_empty() => {
+                app.tags_input.clear();
+                app.tag_suggestion = 0;
+                app.mode = Mode::Tagging;
+            }
+            Some(Action::Tags) => {
                 if let Some(idx) = app.list_state.selected() {
                     if let Some(session) = app.filtered_sessions.get(idx) {
-                        app.tags_input = session.tags.join(", ");
+                        app.tags_input.set(session.tags.join(", "));
+                        app.tag_suggestion = 0;
                         app.mode = Mode::Tagging;
                     }
                 }
             }
-            KeyCode::Char('n') => {
-                if let Some(idx) = app.list_state.selected() {
-                    if let Some(session) = app.filtered_sessions.get(idx) {
-                        app.notes_input = session.notes.clone();
-                        app.mode = Mode::Notes;
-                    }
+            Some(Action::Project) => {
+                if let Some(session) = app.selected_session() {
+                    app.project_input.set(session.project.clone());
+                    app.mode = Mode::Project;
                 }
             }
-            KeyCode::Char('r') => {
+            Some(Action::Mark) => app.toggle_mark(),
+            Some(Action::MarkAll) => app.toggle_mark_all(),
+            Some(Action::ClearMarks) => app.marked.clear(),
+            Some(Action::Export) => app.export_sessions()?,
+            Some(Action::Notes) => {
                 if let Some(idx) = app.list_state.selected() {
                     if let Some(session) = app.filtered_sessions.get(idx) {
-                        if let Some(ratings) = &session.ratings {
-                            app.rating_mental = ratings.mental_energy;
-                            app.rating_physical = ratings.physical_energy;
-                            app.rating_cognitive = ratings.cognitive_load;
-                        } else {
-                            app.rating_mental = 0;
-                            app.rating_physical = 0;
-                            app.rating_cognitive = 0;
-                        }
-                        app.mode = Mode::Rating(RatingField::MentalEnergy);
+                        app.notes_input.set(session.notes.clone());
+                        app.mode = Mode::Notes;
                     }
                 }
             }
-            KeyCode::Char('e') => app.handle_edit_session(terminal)?,
-            KeyCode::Char('a') => {
-                app.creation_duration = "25".to_string();
-                app.creation_description = if let Some(first) = app.sessions.first() {
-                    first.description.clone()
-                } else {
-                    String::new()
-                };
-                app.mode = Mode::Creation(CreationField::Description);
+            Some(Action::Undo) => app.undo()?,
+            Some(Action::Redo) => app.redo()?,
+            Some(Action::Rate) => {
+                app.start_rating();
             }
-            KeyCode::Char('c') => app.cancel_session()?,
-            KeyCode::Char('s') => app.duplicate_and_start_session()?,
-            KeyCode::Char('x') => {
-                if app.list_state.selected().is_some() {
-                    app.mode = Mode::DeleteConfirm;
-                }
+            Some(Action::Edit) => app.handle_edit_session(terminal)?,
+            Some(Action::Create) => app.start_creation(),
+            Some(Action::Log) => app.start_logging(),
+            Some(Action::Finish) => app.finish_session()?,
+            Some(Action::Interrupt) => app.interrupt_session()?,
+            Some(Action::Cancel) => app.cancel_session()?,
+            Some(Action::DuplicateAndStart) => app.duplicate_and_start_session()?,
+            Some(Action::Delete) if app.list_state.selected().is_some() => {
+                app.mode = Mode::DeleteConfirm;
             }
-            KeyCode::Char('f') => {
+            Some(Action::FastFilter) => {
                 app.mode = Mode::FastFilter;
             }
-            KeyCode::Char('z') => {
+            Some(Action::Trash) => app.toggle_trash(),
+            Some(Action::Sort) => app.cycle_sort_key(),
+            Some(Action::ReverseSort) => app.toggle_sort_order(),
+            Some(Action::Group) => app.cycle_group_by(),
+            Some(Action::Restore) if app.show_trash => app.restore_session()?,
+            Some(Action::Zen) => {
                 app.mode = Mode::Zen;
             }
-            KeyCode::Tab => {
-                app.mode = Mode::Input(InputField::Search);
+            Some(Action::Help) => {
+                app.help_scroll = 0;
+                app.mode = Mode::Help;
+            }
+            Some(Action::Bottom) => app.go_to_bottom(),
+            Some(Action::Top) => app.mode = Mode::PendingG,
+            None => {
+                if let KeyCode::Char(c @ '1'..='9') = key.code {
+                    app.start_preset(c as usize - '1' as usize)?;
+                }
             }
-            KeyCode::Char('G') => app.to_bottom(),
-            KeyCode::Char('g') => app.mode = Mode::PendingG,
             _ => {}
         },
-        Mode::PendingG => match key.code {
-            KeyCode::Char('g') => {
-                app.to_top();
+        Mode::PendingG => {
+            if app.keymap.is(Action::Top, &key) {
+                app.go_to_top();
+            }
+            app.mode = Mode::Navigation;
+        }
+        Mode::Help => {
+            if app.keymap.is(Action::Down, &key) {
+                app.help_scroll = app.help_scroll.saturating_add(1);
+            } else if app.keymap.is(Action::Up, &key) {
+                app.help_scroll = app.help_scroll.saturating_sub(1);
+            } else {
                 app.mode = Mode::Navigation;
             }
-            KeyCode::Esc => app.mode = Mode::Navigation,
-            _ => app.mode = Mode::Navigation,
-        },
+        }
         Mode::Input(field) => match key.code {
-            KeyCode::Char(c) => {
-                match field {
-                    InputField::Date => app.date_input.push(c),
-                    InputField::Search => app.search_input.push(c),
-                }
-                app.filter_sessions();
-            }
-            KeyCode::Backspace => {
-                match field {
-                    InputField::Date => {
-                        app.date_input.pop();
-                    }
-                    InputField::Search => {
-                        app.search_input.pop();
-                    }
-                }
-                app.filter_sessions();
-            }
             KeyCode::Esc => app.mode = Mode::Navigation,
             KeyCode::Enter => app.mode = Mode::Navigation,
             KeyCode::Tab => {
@@ -115,61 +123,70 @@ pub fn handle_key_event(
                     InputField::Search => Mode::Input(InputField::Date),
                 }
             }
-            _ => {}
+            _ => {
+                let input = match field {
+                    InputField::Date => &mut app.date_input,
+                    InputField::Search => &mut app.search_input,
+                };
+                if input.handle_key(&key) {
+                    app.filter_sessions();
+                }
+            }
         },
         Mode::Tagging => match key.code {
-            KeyCode::Char(c) => app.tags_input.push(c),
-            KeyCode::Backspace => {
-                app.tags_input.pop();
-            }
             KeyCode::Enter => {
                 app.save_tags()?;
                 app.mode = Mode::Navigation;
             }
             KeyCode::Esc => app.mode = Mode::Navigation,
-            _ => {}
+            KeyCode::Tab => app.complete_tag(),
+            KeyCode::Down => {
+                let count = app.tag_suggestions().len().max(1);
+                app.tag_suggestion = (app.tag_suggestion + 1) % count;
+            }
+            KeyCode::Up => {
+                let count = app.tag_suggestions().len().max(1);
+                app.tag_suggestion = (app.tag_suggestion + count - 1) % count;
+            }
+            _ => {
+                if app.tags_input.handle_key(&key) {
+                    app.tag_suggestion = 0;
+                }
+            }
         },
         Mode::Notes => match key.code {
-            KeyCode::Char(c) => app.notes_input.push(c),
-            KeyCode::Backspace => {
-                app.notes_input.pop();
+            // Enter starts a new line, so notes are saved with Ctrl-S or Alt-Enter
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.save_notes()?;
+                app.mode = Mode::Navigation;
             }
-            KeyCode::Enter => {
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
                 app.save_notes()?;
                 app.mode = Mode::Navigation;
             }
             KeyCode::Esc => app.mode = Mode::Navigation,
-            _ => {}
-        },
-        Mode::Creation(field) => match key.code {
-            KeyCode::Char(c) => match field {
-                CreationField::Duration => app.creation_duration.push(c),
-                CreationField::Description => app.creation_description.push(c),
-            },
-            KeyCode::Backspace => match field {
-                CreationField::Duration => {
-                    app.creation_duration.pop();
-                }
-                CreationField::Description => {
-                    app.creation_description.pop();
-                }
-            },
-            KeyCode::Tab => {
-                app.mode = match field {
-                    CreationField::Duration => Mode::Creation(CreationField::Description),
-                    CreationField::Description => Mode::Creation(CreationField::Duration),
-                }
+            _ => {
+                app.notes_input.handle_key(&key);
             }
+        },
+        Mode::Project => match key.code {
             KeyCode::Enter => {
-                app.create_session()?;
+                app.save_project()?;
                 app.mode = Mode::Navigation;
             }
             KeyCode::Esc => app.mode = Mode::Navigation,
-            _ => {}
+            _ => {
+                app.project_input.handle_key(&key);
+            }
         },
+        Mode::Creation(field) => handle_creation_key(key, field.clone(), app)?,
         Mode::DeleteConfirm => match key.code {
             KeyCode::Char('y') | KeyCode::Enter => {
-                app.delete_session()?;
+                if app.show_trash {
+                    app.purge_session()?;
+                } else {
+                    app.delete_session()?;
+                }
                 app.mode = Mode::Navigation;
             }
             KeyCode::Char('n') | KeyCode::Esc => {
@@ -178,7 +195,7 @@ pub fn handle_key_event(
             _ => {}
         },
         Mode::Rating(field) => match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
+            _ if app.keymap.is(Action::Down, &key) => {
                 app.mode = match field {
                     RatingField::MentalEnergy => Mode::Rating(RatingField::PhysicalEnergy),
                     RatingField::PhysicalEnergy => Mode::Rating(RatingField::CognitiveLoad),
@@ -186,7 +203,7 @@ pub fn handle_key_event(
                     RatingField::Motivation => Mode::Rating(RatingField::MentalEnergy),
                 }
             }
-            KeyCode::Char('k') | KeyCode::Up => {
+            _ if app.keymap.is(Action::Up, &key) => {
                 app.mode = match field {
                     RatingField::MentalEnergy => Mode::Rating(RatingField::Motivation),
                     RatingField::PhysicalEnergy => Mode::Rating(RatingField::MentalEnergy),
@@ -194,7 +211,7 @@ pub fn handle_key_event(
                     RatingField::Motivation => Mode::Rating(RatingField::CognitiveLoad),
                 }
             }
-            KeyCode::Char('l') | KeyCode::Right => match field {
+            _ if app.keymap.is(Action::Increase, &key) => match field {
                 RatingField::MentalEnergy => app.rating_mental = (app.rating_mental + 1).min(5),
                 RatingField::PhysicalEnergy => {
                     app.rating_physical = (app.rating_physical + 1).min(5)
@@ -206,7 +223,7 @@ pub fn handle_key_event(
                     app.rating_motivation = (app.rating_motivation + 1).min(5)
                 }
             },
-            KeyCode::Char('h') | KeyCode::Left => match field {
+            _ if app.keymap.is(Action::Decrease, &key) => match field {
                 RatingField::MentalEnergy => {
                     app.rating_mental = app.rating_mental.saturating_sub(1)
                 }
@@ -230,34 +247,217 @@ pub fn handle_key_event(
         Mode::FastFilter => match key.code {
             KeyCode::Char('t') => {
                 let today = Utc::now().date_naive();
-                app.date_input = today.format("%Y-%m-%d").to_string();
+                app.date_input.set(today.format("%Y-%m-%d").to_string());
                 app.filter_sessions();
                 app.mode = Mode::Navigation;
             }
             KeyCode::Char('w') => {
                 let today = Utc::now().date_naive();
                 let week_ago = today - chrono::Duration::days(7);
-                app.date_input = format!(
+                app.date_input.set(format!(
                     "{} - {}",
                     week_ago.format("%Y-%m-%d"),
                     today.format("%Y-%m-%d")
-                );
+                ));
                 app.filter_sessions();
                 app.mode = Mode::Navigation;
             }
             KeyCode::Char('c') => {
-                app.date_input = String::new();
+                app.date_input.clear();
                 app.filter_sessions();
                 app.mode = Mode::Navigation;
             }
+            KeyCode::Char('s') => {
+                app.view_name_input.clear();
+                app.mode = Mode::SaveView;
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                app.apply_view(c as usize - '1' as usize);
+                app.mode = Mode::Navigation;
+            }
             KeyCode::Esc => app.mode = Mode::Navigation,
             _ => {}
         },
-        Mode::Zen => match key.code {
-            KeyCode::Char('z') | KeyCode::Esc => app.mode = Mode::Navigation,
-            KeyCode::Char('q') => return Ok(false),
-            _ => {}
+        Mode::SaveView => match key.code {
+            KeyCode::Enter => {
+                app.save_view()?;
+                app.mode = Mode::Navigation;
+            }
+            KeyCode::Esc => app.mode = Mode::Navigation,
+            _ => {
+                app.view_name_input.handle_key(&key);
+            }
         },
+        Mode::Zen => {
+            if key.code == KeyCode::Esc || app.keymap.is(Action::Zen, &key) {
+                app.mode = Mode::Navigation;
+            } else if app.keymap.is(Action::Quit, &key) {
+                return Ok(false);
+            } else if key.code == KeyCode::Enter
+                && !app.sessions.iter().any(|s| s.state == SessionState::Running)
+            {
+                app.start_next_session()?;
+            }
+        }
     }
     Ok(true)
 }
+
+/// Keys of the creation and log form.
+pub fn handle_creation_key(
+    key: KeyEvent,
+    field: CreationField,
+    app: &mut App,
+) -> Result<(), Box<dyn Error>> {
+    match key.code {
+        KeyCode::Tab => {
+            app.description_suggestion = None;
+            app.mode = match field {
+                CreationField::Description => Mode::Creation(CreationField::Duration),
+                CreationField::Duration if app.creation_log => {
+                    Mode::Creation(CreationField::Start)
+                }
+                CreationField::Duration | CreationField::Start => {
+                    Mode::Creation(CreationField::Description)
+                }
+            }
+        }
+        KeyCode::Enter
+            if field == CreationField::Description && app.description_suggestion.is_some() =>
+        {
+            app.complete_description()
+        }
+        KeyCode::Enter if app.creation_log => {
+            if app.log_session()? {
+                app.mode = Mode::Navigation;
+            }
+        }
+        KeyCode::Enter => {
+            app.create_session()?;
+            app.mode = Mode::Navigation;
+        }
+        KeyCode::Esc => app.mode = Mode::Navigation,
+        KeyCode::Down if field == CreationField::Description => {
+            let count = app.description_suggestions().len();
+            app.description_suggestion = match app.description_suggestion {
+                _ if count == 0 => None,
+                Some(i) if i + 1 < count => Some(i + 1),
+                Some(_) => None,
+                None => Some(0),
+            };
+        }
+        KeyCode::Up if field == CreationField::Description => {
+            let count = app.description_suggestions().len();
+            app.description_suggestion = match app.description_suggestion {
+                _ if count == 0 => None,
+                Some(0) => None,
+                Some(i) => Some(i - 1),
+                None => Some(count - 1),
+            };
+        }
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.cycle_creation_preset(true);
+        }
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.cycle_creation_preset(false);
+        }
+        _ => {
+            match field {
+                CreationField::Duration => {
+                    app.creation_duration.handle_key(&key);
+                }
+                CreationField::Start => {
+                    app.creation_start.handle_key(&key);
+                }
+                CreationField::Description => {
+                    if app.creation_description.handle_key(&key) {
+                        app.description_suggestion = None;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_mouse_event(mouse: MouseEvent, app: &mut App) -> Result<(), Box<dyn Error>> {
+    let position = Position::new(mouse.column, mouse.row);
+
+    match (&app.mode, mouse.kind) {
+        (Mode::Help, MouseEventKind::ScrollDown) => {
+            app.help_scroll = app.help_scroll.saturating_add(1);
+        }
+        (Mode::Help, MouseEventKind::ScrollUp) => {
+            app.help_scroll = app.help_scroll.saturating_sub(1);
+        }
+        (Mode::Navigation, MouseEventKind::ScrollDown) => app.next(),
+        (Mode::Navigation, MouseEventKind::ScrollUp) => app.previous(),
+        (
+            Mode::Navigation | Mode::Input(_) | Mode::Rating(_),
+            MouseEventKind::Down(MouseButton::Left),
+        ) => {
+            if app.areas.date_input.contains(position) && !matches!(app.mode, Mode::Rating(_)) {
+                app.mode = Mode::Input(InputField::Date);
+            } else if app.areas.search_input.contains(position)
+                && !matches!(app.mode, Mode::Rating(_))
+            {
+                app.mode = Mode::Input(InputField::Search);
+            } else if app.areas.list.contains(position) && !matches!(app.mode, Mode::Rating(_)) {
+                if let Some(idx) = clicked_session(app, position) {
+                    app.list_state.select(Some(idx));
+                }
+                app.mode = Mode::Navigation;
+            } else if let Some((field, value)) = clicked_star(app.areas.ratings, position) {
+                if app.mode != Mode::Navigation || app.start_rating() {
+                    app.set_rating(&field, value);
+                    app.mode = Mode::Rating(field);
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Index into `filtered_sessions` of the clicked list row, group headers select nothing.
+fn clicked_session(app: &App, position: Position) -> Option<usize> {
+    let list = app.areas.list;
+    // Skip the block border
+    let row = position.y.checked_sub(list.y + 1)?;
+    if row + 2 >= list.height {
+        return None;
+    }
+    match list_rows(app).get(row as usize + app.display_list_state.offset()) {
+        Some(ListRow::Session(idx)) => Some(*idx),
+        _ => None,
+    }
+}
+
+/// Rating line and star value under the mouse in the ratings pane.
+fn clicked_star(area: Rect, position: Position) -> Option<(RatingField, u8)> {
+    if !area.contains(position) {
+        return None;
+    }
+    let field = match position.y.checked_sub(area.y + 1)? {
+        0 => RatingField::MentalEnergy,
+        1 => RatingField::PhysicalEnergy,
+        2 => RatingField::CognitiveLoad,
+        3 => RatingField::Motivation,
+        _ => return None,
+    };
+    let star = position.x.checked_sub(area.x + 1 + info_pane::STARS_OFFSET)?;
+    if star < 5 {
+        Some((field, star as u8 + 1))
+    } else {
+        None
+    }
+}
+
+pub fn handle_paste(text: &str, app: &mut App) {
+    if let Some(input) = app.active_input() {
+        input.insert_str(text);
+        if matches!(app.mode, Mode::Input(_)) {
+            app.filter_sessions();
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::Datelike;
+use std::cmp::Ordering;
+
+use crate::session::{Session, SessionState};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortKey {
+    Start,
+    Duration,
+    Description,
+    Rating,
+    State,
+}
+
+impl SortKey {
+    pub fn next(self) -> SortKey {
+        match self {
+            SortKey::Start => SortKey::Duration,
+            SortKey::Duration => SortKey::Description,
+            SortKey::Description => SortKey::Rating,
+            SortKey::Rating => SortKey::State,
+            SortKey::State => SortKey::Start,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Start => "start",
+            SortKey::Duration => "duration",
+            SortKey::Description => "description",
+            SortKey::Rating => "rating",
+            SortKey::State => "state",
+        }
+    }
+
+    /// Natural order of the key: newest, longest and best rated first, descriptions A to Z.
+    fn compare(self, a: &Session, b: &Session) -> Ordering {
+        match self {
+            SortKey::Start => b.start.cmp(&a.start),
+            SortKey::Duration => b.duration.cmp(&a.duration),
+            SortKey::Description => a.description.to_lowercase().cmp(&b.description.to_lowercase()),
+            SortKey::Rating => average_rating(b).total_cmp(&average_rating(a)),
+            SortKey::State => state_rank(&a.state).cmp(&state_rank(&b.state)),
+        }
+        .then_with(|| b.start.cmp(&a.start))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GroupBy {
+    None,
+    Day,
+    Week,
+    Tag,
+    Project,
+}
+
+impl GroupBy {
+    pub fn next(self) -> GroupBy {
+        match self {
+            GroupBy::None => GroupBy::Day,
+            GroupBy::Day => GroupBy::Week,
+            GroupBy::Week => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::Project,
+            GroupBy::Project => GroupBy::None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::None => "none",
+            GroupBy::Day => "day",
+            GroupBy::Week => "week",
+            GroupBy::Tag => "tag",
+            GroupBy::Project => "project",
+        }
+    }
+
+    /// Name of the group a session belongs to.
+    /// Sessions with several tags are grouped by the first one. Tags and projects are
+    /// lowercased, so names differing only in case share one group.
+    pub fn group_name(self, session: &Session) -> String {
+        match self {
+            GroupBy::None => String::new(),
+            GroupBy::Day => session.start.format("%Y-%m-%d %a").to_string(),
+            GroupBy::Week => {
+                let week = session.start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            GroupBy::Tag => session
+                .tags
+                .first()
+                .map(|tag| tag.to_lowercase())
+                .unwrap_or_else(|| "untagged".to_string()),
+            GroupBy::Project => {
+                if session.project.is_empty() {
+                    "no project".to_string()
+                } else {
+                    session.project.to_lowercase()
+                }
+            }
+        }
+    }
+
+    fn compare(self, a: &Session, b: &Session) -> Ordering {
+        match self {
+            GroupBy::None => Ordering::Equal,
+            // Newest days and weeks first, names A to Z
+            GroupBy::Day | GroupBy::Week => self.group_name(b).cmp(&self.group_name(a)),
+            GroupBy::Tag | GroupBy::Project => self.group_name(a).cmp(&self.group_name(b)),
+        }
+    }
+}
+
+fn average_rating(session: &Session) -> f32 {
+    match &session.ratings {
+        Some(r) => {
+            let sum = [r.mental_energy, r.physical_energy, r.cognitive_load, r.motivation]
+                .into_iter()
+                .map(u32::from)
+                .sum::<u32>();
+            sum as f32 / 4.0
+        }
+        None => -1.0,
+    }
+}
+
+fn state_rank(state: &SessionState) -> u8 {
+    match state {
+        SessionState::Running => 0,
+        SessionState::Planned => 1,
+        SessionState::Done => 2,
+        SessionState::Canceled => 3,
+        SessionState::Deleted => 4,
+    }
+}
+
+/// Sorts sessions so that groups are contiguous and ordered by `sort_key` within each group.
+pub fn sort_sessions(
+    sessions: &mut [Session],
+    sort_key: SortKey,
+    reversed: bool,
+    group_by: GroupBy,
+) {
+    sessions.sort_by(|a, b| {
+        let within_group = if reversed {
+            sort_key.compare(b, a)
+        } else {
+            sort_key.compare(a, b)
+        };
+        group_by.compare(a, b).then(within_group)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionRatings;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    fn session(description: &str, day: u32, minutes: u64) -> Session {
+        let start = Utc.with_ymd_and_hms(2025, 5, day, 14, 0, 0).unwrap();
+        Session::new(description.to_string(), Duration::from_secs(minutes * 60), start)
+    }
+
+    fn rated(description: &str, rating: u8) -> Session {
+        Session {
+            ratings: Some(SessionRatings {
+                mental_energy: rating,
+                physical_energy: rating,
+                cognitive_load: rating,
+                motivation: rating,
+            }),
+            ..session(description, 1, 25)
+        }
+    }
+
+    fn descriptions(sessions: &[Session]) -> Vec<&str> {
+        sessions.iter().map(|s| s.description.as_str()).collect()
+    }
+
+    #[test]
+    fn sorts_by_key_and_reverses() {
+        let mut sessions = vec![session("b", 1, 50), session("A", 3, 10), session("c", 2, 25)];
+        sort_sessions(&mut sessions, SortKey::Start, false, GroupBy::None);
+        assert_eq!(descriptions(&sessions), ["A", "c", "b"]);
+        sort_sessions(&mut sessions, SortKey::Duration, false, GroupBy::None);
+        assert_eq!(descriptions(&sessions), ["b", "c", "A"]);
+        sort_sessions(&mut sessions, SortKey::Description, false, GroupBy::None);
+        assert_eq!(descriptions(&sessions), ["A", "b", "c"]);
+        sort_sessions(&mut sessions, SortKey::Description, true, GroupBy::None);
+        assert_eq!(descriptions(&sessions), ["c", "b", "A"]);
+    }
+
+    #[test]
+    fn rating_sort_puts_unrated_last_and_survives_hand_edited_values() {
+        let mut sessions = vec![session("unrated", 1, 25), rated("low", 1), rated("huge", 250)];
+        assert_eq!(average_rating(&sessions[2]), 250.0);
+        sort_sessions(&mut sessions, SortKey::Rating, false, GroupBy::None);
+        assert_eq!(descriptions(&sessions), ["huge", "low", "unrated"]);
+    }
+
+    #[test]
+    fn groups_stay_contiguous_and_sorted_within() {
+        let mut sessions = vec![
+            session("day 1 short", 1, 10),
+            session("day 2", 2, 25),
+            session("day 1 long", 1, 50),
+        ];
+        sort_sessions(&mut sessions, SortKey::Duration, false, GroupBy::Day);
+        assert_eq!(descriptions(&sessions), ["day 2", "day 1 long", "day 1 short"]);
+        assert_eq!(GroupBy::Day.group_name(&sessions[0]), "2025-05-02 Fri");
+        assert_eq!(GroupBy::Week.group_name(&sessions[0]), "2025-W18");
+    }
+
+    #[test]
+    fn tags_differing_in_case_share_one_group() {
+        let tagged = |description: &str, tag: &str, day| Session {
+            tags: vec![tag.to_string()],
+            ..session(description, day, 25)
+        };
+        let mut sessions = vec![
+            tagged("one", "Rust", 1),
+            tagged("two", "go", 2),
+            tagged("three", "rust", 3),
+            session("four", 4, 25),
+        ];
+        sort_sessions(&mut sessions, SortKey::Start, false, GroupBy::Tag);
+        let groups: Vec<String> = sessions.iter().map(|s| GroupBy::Tag.group_name(s)).collect();
+        assert_eq!(groups, ["go", "rust", "rust", "untagged"]);
+        assert_eq!(descriptions(&sessions), ["two", "three", "one", "four"]);
+    }
+
+    #[test]
+    fn sessions_without_project_are_grouped_together() {
+        let mut with_project = session("one", 1, 25);
+        with_project.project = "Acme".to_string();
+        assert_eq!(GroupBy::Project.group_name(&with_project), "acme");
+        assert_eq!(GroupBy::Project.group_name(&session("two", 1, 25)), "no project");
+    }
+}
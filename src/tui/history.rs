@@ -0,0 +1,160 @@
+use crate::session::Session;
+
+const MAX_ENTRIES: usize = 100;
+
+/// One session before and after an edit. `None` means the session file doesn't exist.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub before: Option<Session>,
+    pub after: Option<Session>,
+}
+
+impl Change {
+    pub fn inverted(&self) -> Change {
+        Change {
+            before: self.after.clone(),
+            after: self.before.clone(),
+        }
+    }
+}
+
+/// Undo and redo stacks of TUI edits. One entry holds all sessions touched by one action.
+#[derive(Debug, Default)]
+pub struct History {
+    undo_stack: Vec<Vec<Change>>,
+    redo_stack: Vec<Vec<Change>>,
+}
+
+impl History {
+    pub fn record(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+        self.undo_stack.push(changes);
+        if self.undo_stack.len() > MAX_ENTRIES {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Returns the changes that revert the last action, in the order they have to be applied.
+    pub fn undo(&mut self) -> Option<Vec<Change>> {
+        let changes = self.undo_stack.pop()?;
+        let reverted = changes.iter().rev().map(Change::inverted).collect();
+        self.redo_stack.push(changes);
+        Some(reverted)
+    }
+
+    /// Returns the changes of the last undone action.
+    pub fn redo(&mut self) -> Option<Vec<Change>> {
+        let changes = self.redo_stack.pop()?;
+        self.undo_stack.push(changes.clone());
+        Some(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    fn session(description: &str) -> Session {
+        Session::new(description.to_string(), Duration::from_secs(60), Utc::now())
+    }
+
+    fn edit(before: &str, after: &str) -> Change {
+        Change {
+            before: Some(session(before)),
+            after: Some(session(after)),
+        }
+    }
+
+    fn descriptions(changes: &[Change]) -> Vec<(Option<String>, Option<String>)> {
+        changes
+            .iter()
+            .map(|c| {
+                (
+                    c.before.as_ref().map(|s| s.description.clone()),
+                    c.after.as_ref().map(|s| s.description.clone()),
+                )
+            })
+            .collect()
+    }
+
+    fn pair(before: &str, after: &str) -> (Option<String>, Option<String>) {
+        (Some(before.to_string()), Some(after.to_string()))
+    }
+
+    #[test]
+    fn undo_inverts_and_redo_reapplies() {
+        let mut history = History::default();
+        history.record(vec![edit("a", "b")]);
+        assert_eq!(descriptions(&history.undo().unwrap()), [pair("b", "a")]);
+        assert!(history.undo().is_none());
+        assert_eq!(descriptions(&history.redo().unwrap()), [pair("a", "b")]);
+        assert!(history.redo().is_none());
+        assert!(history.undo().is_some());
+    }
+
+    #[test]
+    fn creating_and_deleting_files_swap_on_undo() {
+        let mut history = History::default();
+        history.record(vec![Change {
+            before: None,
+            after: Some(session("new")),
+        }]);
+        let undo = history.undo().unwrap();
+        assert!(undo[0].before.is_some());
+        assert!(undo[0].after.is_none());
+    }
+
+    #[test]
+    fn a_new_change_clears_redo() {
+        let mut history = History::default();
+        history.record(vec![edit("a", "b")]);
+        history.undo();
+        history.record(vec![edit("a", "c")]);
+        assert!(history.redo().is_none());
+        assert_eq!(descriptions(&history.undo().unwrap()), [pair("c", "a")]);
+    }
+
+    #[test]
+    fn empty_changes_are_not_recorded() {
+        let mut history = History::default();
+        history.record(vec![edit("a", "b")]);
+        history.undo();
+        history.record(Vec::new());
+        assert!(history.redo().is_some());
+    }
+
+    #[test]
+    fn bulk_changes_undo_together_in_reverse_order() {
+        let mut history = History::default();
+        history.record(vec![edit("a1", "a2"), edit("b1", "b2"), edit("c1", "c2")]);
+        assert_eq!(
+            descriptions(&history.undo().unwrap()),
+            [pair("c2", "c1"), pair("b2", "b1"), pair("a2", "a1")]
+        );
+        assert_eq!(
+            descriptions(&history.redo().unwrap()),
+            [pair("a1", "a2"), pair("b1", "b2"), pair("c1", "c2")]
+        );
+    }
+
+    #[test]
+    fn depth_is_bounded_and_drops_the_oldest_entries() {
+        let mut history = History::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.record(vec![edit(&i.to_string(), &(i + 1).to_string())]);
+        }
+        let mut undone = 0;
+        let mut last = None;
+        while let Some(changes) = history.undo() {
+            undone += 1;
+            last = changes[0].after.as_ref().map(|s| s.description.clone());
+        }
+        assert_eq!(undone, MAX_ENTRIES);
+        assert_eq!(last.as_deref(), Some("5"));
+    }
+}
@@ -0,0 +1,521 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Actions of the navigation mode that can be bound to keys in the `[keymap]` config section.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Down,
+    Up,
+    Top,
+    Bottom,
+    Search,
+    DateFilter,
+    FastFilter,
+    Tags,
+    Notes,
+    Project,
+    Rate,
+    Create,
+    Log,
+    Edit,
+    DuplicateAndStart,
+    Finish,
+    Interrupt,
+    Cancel,
+    Delete,
+    Restore,
+    Mark,
+    MarkAll,
+    ClearMarks,
+    Export,
+    Undo,
+    Redo,
+    Trash,
+    Sort,
+    ReverseSort,
+    Group,
+    Zen,
+    Help,
+    Quit,
+    Increase,
+    Decrease,
+}
+
+impl Action {
+    pub const ALL: [Action; 35] = [
+        Action::Down,
+        Action::Up,
+        Action::Top,
+        Action::Bottom,
+        Action::Search,
+        Action::DateFilter,
+        Action::FastFilter,
+        Action::Tags,
+        Action::Notes,
+        Action::Project,
+        Action::Rate,
+        Action::Create,
+        Action::Log,
+        Action::Edit,
+        Action::DuplicateAndStart,
+        Action::Finish,
+        Action::Interrupt,
+        Action::Cancel,
+        Action::Delete,
+        Action::Restore,
+        Action::Mark,
+        Action::MarkAll,
+        Action::ClearMarks,
+        Action::Export,
+        Action::Undo,
+        Action::Redo,
+        Action::Trash,
+        Action::Sort,
+        Action::ReverseSort,
+        Action::Group,
+        Action::Zen,
+        Action::Help,
+        Action::Quit,
+        Action::Increase,
+        Action::Decrease,
+    ];
+
+    /// Name used in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Search => "search",
+            Action::DateFilter => "date_filter",
+            Action::FastFilter => "fast_filter",
+            Action::Tags => "tags",
+            Action::Notes => "notes",
+            Action::Project => "project",
+            Action::Rate => "rate",
+            Action::Create => "create",
+            Action::Log => "log",
+            Action::Edit => "edit",
+            Action::DuplicateAndStart => "duplicate_and_start",
+            Action::Finish => "finish",
+            Action::Interrupt => "interrupt",
+            Action::Cancel => "cancel",
+            Action::Delete => "delete",
+            Action::Restore => "restore",
+            Action::Mark => "mark",
+            Action::MarkAll => "mark_all",
+            Action::ClearMarks => "clear_marks",
+            Action::Export => "export",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Trash => "trash",
+            Action::Sort => "sort",
+            Action::ReverseSort => "reverse_sort",
+            Action::Group => "group",
+            Action::Zen => "zen",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Increase => "increase",
+            Action::Decrease => "decrease",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Top => "top (press twice)",
+            Action::Bottom => "bottom",
+            Action::Search => "search",
+            Action::DateFilter => "date filter",
+            Action::FastFilter => "fast filter",
+            Action::Tags => "tags",
+            Action::Notes => "notes",
+            Action::Project => "project",
+            Action::Rate => "rate",
+            Action::Create => "create",
+            Action::Log => "log a past session",
+            Action::Edit => "edit in $EDITOR",
+            Action::DuplicateAndStart => "duplicate & start selected",
+            Action::Finish => "finish running session now",
+            Action::Interrupt => "interrupt running session",
+            Action::Cancel => "cancel",
+            Action::Delete => "delete",
+            Action::Restore => "restore (trash)",
+            Action::Mark => "mark",
+            Action::MarkAll => "mark all",
+            Action::ClearMarks => "clear marks",
+            Action::Export => "export",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Trash => "trash",
+            Action::Sort => "sort key",
+            Action::ReverseSort => "reverse sort",
+            Action::Group => "group",
+            Action::Zen => "zen",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Increase => "increase rating",
+            Action::Decrease => "decrease rating",
+        }
+    }
+
+    fn default_keys(self) -> &'static str {
+        match self {
+            Action::Down => "j down",
+            Action::Up => "k up",
+            Action::Top => "g",
+            Action::Bottom => "G",
+            Action::Search => "/ tab",
+            Action::DateFilter => "i",
+            Action::FastFilter => "f",
+            Action::Tags => "t",
+            Action::Notes => "n",
+            Action::Project => "p",
+            Action::Rate => "r",
+            Action::Create => "a",
+            Action::Log => "L",
+            Action::Edit => "e",
+            Action::DuplicateAndStart => "s",
+            Action::Finish => "F",
+            Action::Interrupt => "I",
+            Action::Cancel => "c",
+            Action::Delete => "x",
+            Action::Restore => "R",
+            Action::Mark => "space",
+            Action::MarkAll => "V",
+            Action::ClearMarks => "esc",
+            Action::Export => "E",
+            Action::Undo => "u",
+            Action::Redo => "ctrl-r",
+            Action::Trash => "T",
+            Action::Sort => "o",
+            Action::ReverseSort => "O",
+            Action::Group => "b",
+            Action::Zen => "z",
+            Action::Help => "?",
+            Action::Quit => "q",
+            Action::Increase => "l right",
+            Action::Decrease => "h left",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// Rating keys only act inside the rating form and may reuse navigation keys.
+    fn is_rating_only(self) -> bool {
+        matches!(self, Action::Increase | Action::Decrease)
+    }
+
+    /// Actions the rating form reacts to, so their keys may not overlap either.
+    fn is_used_in_rating(self) -> bool {
+        matches!(self, Action::Up | Action::Down | Action::Increase | Action::Decrease)
+    }
+}
+
+/// Actions shown in the keybind bar, everything else is listed in the help overlay.
+const BAR_ACTIONS: [Action; 13] = [
+    Action::Down,
+    Action::Search,
+    Action::DateFilter,
+    Action::Tags,
+    Action::Notes,
+    Action::Rate,
+    Action::Create,
+    Action::DuplicateAndStart,
+    Action::Delete,
+    Action::Undo,
+    Action::FastFilter,
+    Action::Help,
+    Action::Quit,
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Key {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl Key {
+    /// Parses `j`, `G`, `?`, `space`, `esc`, `tab`, `enter`, arrow names and `ctrl-<key>`.
+    pub fn parse(input: &str) -> Result<Key, String> {
+        let (ctrl, name) = match input.strip_prefix("ctrl-") {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let code = match name.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Enter,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key '{}'", input)),
+                }
+            }
+        };
+        Ok(Key { code, ctrl })
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.ctrl == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn label(&self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            code => format!("{:?}", code),
+        };
+        if self.ctrl {
+            format!("C-{}", name)
+        } else {
+            name
+        }
+    }
+}
+
+/// Keys bound to each action, the defaults overridden by the `[keymap]` config section.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = parse_keys(action.default_keys()).expect("valid default keys");
+                (action, keys)
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from `action = "key key"` pairs, unmentioned actions keep their defaults.
+    pub fn from_config(overrides: &HashMap<String, String>) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+        for (name, keys) in overrides {
+            let action =
+                Action::from_name(name).ok_or_else(|| format!("unknown keymap action '{}'", name))?;
+            let keys = parse_keys(keys).map_err(|e| format!("keymap '{}': {}", name, e))?;
+            if let Some(binding) = keymap.bindings.iter_mut().find(|(a, _)| *a == action) {
+                binding.1 = keys;
+            }
+        }
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    fn check_conflicts(&self) -> Result<(), String> {
+        for (i, (action, keys)) in self.bindings.iter().enumerate() {
+            for (other, other_keys) in &self.bindings[i + 1..] {
+                let both_in_navigation = !action.is_rating_only() && !other.is_rating_only();
+                let both_in_rating = action.is_used_in_rating() && other.is_used_in_rating();
+                if !both_in_navigation && !both_in_rating {
+                    continue;
+                }
+                if let Some(key) = keys.iter().find(|k| other_keys.contains(k)) {
+                    return Err(format!(
+                        "key '{}' is bound to both '{}' and '{}'",
+                        key.label(),
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The navigation action bound to a key press.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| !action.is_rating_only())
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether the key press is bound to the action, also for actions that share keys.
+    pub fn is(&self, action: Action, event: &KeyEvent) -> bool {
+        self.keys(action).iter().any(|k| k.matches(event))
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn label(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .map(Key::label)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    pub fn bar_text(&self) -> String {
+        BAR_ACTIONS
+            .iter()
+            .map(|action| match action {
+                Action::Down => format!(
+                    "{}/{}: down/up",
+                    self.first_label(Action::Down),
+                    self.first_label(Action::Up)
+                ),
+                action => format!("{}: {}", self.first_label(*action), action.description()),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    fn first_label(&self, action: Action) -> String {
+        self.keys(action).first().map(Key::label).unwrap_or_default()
+    }
+
+    /// Every binding, grouped by the mode it applies in.
+    pub fn help_sections(&self) -> Vec<(&'static str, Vec<(String, String)>)> {
+        let fixed = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, d)| (k.to_string(), d.to_string()))
+                .collect()
+        };
+        let mut navigation: Vec<(String, String)> = Action::ALL
+            .into_iter()
+            .filter(|a| !a.is_rating_only())
+            .map(|a| (self.label(a), a.description().to_string()))
+            .collect();
+        navigation.push(("1-9".to_string(), "start preset".to_string()));
+
+        vec![
+            ("Navigation", navigation),
+            (
+                "Search and date filter",
+                fixed(&[("Tab", "switch field"), ("Enter/Esc", "back to the list")]),
+            ),
+            (
+                "Text inputs",
+                fixed(&[
+                    ("←/→", "move cursor"),
+                    ("C-←/C-→", "move by word"),
+                    ("Home/C-a", "start of line"),
+                    ("End/C-e", "end of line"),
+                    ("C-w", "delete word"),
+                    ("C-u", "delete to start of line"),
+                    ("Delete", "delete next character"),
+                ]),
+            ),
+            (
+                "Tags",
+                fixed(&[
+                    ("Tab", "complete tag"),
+                    ("↑/↓", "choose completion"),
+                    ("Enter", "save"),
+                    ("Esc", "cancel"),
+                ]),
+            ),
+            (
+                "Project, view name",
+                fixed(&[("Enter", "save"), ("Esc", "cancel")]),
+            ),
+            (
+                "Notes",
+                fixed(&[
+                    ("Enter", "new line"),
+                    ("↑/↓", "previous/next line"),
+                    ("C-s/Alt-Enter", "save"),
+                    ("Esc", "cancel"),
+                ]),
+            ),
+            (
+                "Create and log session",
+                fixed(&[
+                    ("Tab", "switch field"),
+                    ("C-n/C-p", "next/previous preset"),
+                    ("↑/↓", "choose a past description"),
+                    ("Enter", "use the chosen description, or create"),
+                    ("Esc", "cancel"),
+                ]),
+            ),
+            (
+                "Rating",
+                vec![
+                    (self.label(Action::Down), "next rating".to_string()),
+                    (self.label(Action::Up), "previous rating".to_string()),
+                    (self.label(Action::Increase), "increase".to_string()),
+                    (self.label(Action::Decrease), "decrease".to_string()),
+                    ("Enter".to_string(), "save".to_string()),
+                    ("Esc".to_string(), "cancel".to_string()),
+                ],
+            ),
+            (
+                "Delete confirmation",
+                fixed(&[("y/Enter", "confirm"), ("n/Esc", "cancel")]),
+            ),
+            (
+                "Fast filter",
+                fixed(&[
+                    ("t", "today"),
+                    ("w", "last week"),
+                    ("c", "clear filter"),
+                    ("s", "save as view"),
+                    ("1-9", "apply view"),
+                    ("Esc", "cancel"),
+                ]),
+            ),
+            (
+                "Zen",
+                vec![
+                    (format!("{}/Esc", self.label(Action::Zen)), "back".to_string()),
+                    ("Enter".to_string(), "start the next pomodoro".to_string()),
+                    (self.label(Action::Quit), "quit".to_string()),
+                ],
+            ),
+        ]
+    }
+}
+
+fn parse_keys(input: &str) -> Result<Vec<Key>, String> {
+    let keys = input
+        .split_whitespace()
+        .map(Key::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err("no keys given".to_string());
+    }
+    Ok(keys)
+}
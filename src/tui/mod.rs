@@ -1,3 +1,8 @@
 pub mod app;
 pub mod components;
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod grouping;
+pub mod history;
+pub mod keymap;
+pub mod text_input;
+pub mod theme;
\ No newline at end of file
@@ -0,0 +1,373 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthChar;
+
+/// Editable text with a cursor, shared by all TUI inputs.
+///
+/// The cursor is a byte index that always sits on a char boundary. Single line inputs
+/// turn pasted newlines into spaces, multi-line inputs keep them and insert one on Enter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+    multiline: bool,
+}
+
+impl TextInput {
+    pub fn new() -> TextInput {
+        TextInput::default()
+    }
+
+    pub fn multiline() -> TextInput {
+        TextInput {
+            multiline: true,
+            ..TextInput::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces a byte range of the text and puts the cursor after the replacement.
+    pub fn replace_range(&mut self, range: std::ops::Range<usize>, replacement: &str) {
+        let end = range.start + replacement.len();
+        self.text.replace_range(range, replacement);
+        self.cursor = end;
+    }
+
+    /// Replaces the text and puts the cursor at its end.
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        if !self.multiline {
+            self.text = self.text.replace(['\r', '\n'], " ");
+        }
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Inserts pasted text at the cursor.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = if self.multiline {
+            text
+        } else {
+            text.replace('\n', " ")
+        };
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    /// Applies an editing or movement key, false for keys the input doesn't use like Esc.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('w') if ctrl => self.delete_word_before(),
+            KeyCode::Char('u') if ctrl => {
+                let start = self.line_start();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Char('a') if ctrl => self.cursor = self.line_start(),
+            KeyCode::Char('e') if ctrl => self.cursor = self.line_end(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter if self.multiline && key.modifiers.is_empty() => self.insert('\n'),
+            KeyCode::Backspace if ctrl => self.delete_word_before(),
+            KeyCode::Backspace => {
+                if let Some(c) = self.text[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                    self.text.remove(self.cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if self.cursor < self.text.len() {
+                    self.text.remove(self.cursor);
+                }
+            }
+            KeyCode::Left if ctrl => self.cursor = self.word_start_before(),
+            KeyCode::Right if ctrl => self.cursor = self.word_end_after(),
+            KeyCode::Left => {
+                if let Some(c) = self.text[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                }
+            }
+            KeyCode::Right => {
+                if let Some(c) = self.text[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+            }
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End => self.cursor = self.line_end(),
+            KeyCode::Up if self.multiline => self.move_line(false),
+            KeyCode::Down if self.multiline => self.move_line(true),
+            _ => return false,
+        }
+        true
+    }
+
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i)
+    }
+
+    fn word_start_before(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let trimmed = before.trim_end();
+        trimmed
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + trimmed[i..].chars().next().map_or(1, char::len_utf8))
+    }
+
+    fn word_end_after(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let skipped = after.len() - after.trim_start().len();
+        let word = &after[skipped..];
+        let end = word.find(char::is_whitespace).unwrap_or(word.len());
+        self.cursor + skipped + end
+    }
+
+    fn delete_word_before(&mut self) {
+        let start = self.word_start_before();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Moves to the same display column on the previous or next line.
+    fn move_line(&mut self, down: bool) {
+        let start = self.line_start();
+        let column = display_width(&self.text[start..self.cursor]);
+        let target_start = if down {
+            match self.text[self.cursor..].find('\n') {
+                Some(i) => self.cursor + i + 1,
+                None => return,
+            }
+        } else {
+            if start == 0 {
+                return;
+            }
+            self.text[..start - 1].rfind('\n').map_or(0, |i| i + 1)
+        };
+
+        let mut cursor = target_start;
+        let mut width = 0;
+        for c in self.text[target_start..].chars() {
+            let char_width = c.width().unwrap_or(0);
+            if c == '\n' || width + char_width > column {
+                break;
+            }
+            width += char_width;
+            cursor += c.len_utf8();
+        }
+        self.cursor = cursor;
+    }
+
+    /// Visible lines for an area of `width` x `height` cells and the cursor inside it.
+    ///
+    /// Single line inputs scroll horizontally, multi-line inputs wrap at the width
+    /// and scroll vertically so the cursor stays visible.
+    pub fn view(&self, width: u16, height: u16) -> (Vec<String>, (u16, u16)) {
+        let width = width.max(1) as usize;
+        if !self.multiline {
+            let cursor_column = display_width(&self.text[..self.cursor]);
+            let mut skipped = 0;
+            let mut chars = self.text.chars();
+            while cursor_column - skipped >= width {
+                match chars.next() {
+                    Some(c) => skipped += c.width().unwrap_or(0),
+                    None => break,
+                }
+            }
+            let mut visible = String::new();
+            let mut visible_width = 0;
+            for c in chars {
+                let char_width = c.width().unwrap_or(0);
+                if visible_width + char_width > width {
+                    break;
+                }
+                visible_width += char_width;
+                visible.push(c);
+            }
+            return (vec![visible], ((cursor_column - skipped) as u16, 0));
+        }
+
+        let mut rows = vec![String::new()];
+        let mut column = 0;
+        let mut cursor = None;
+        for (idx, c) in self.text.char_indices() {
+            if c == '\n' {
+                if idx == self.cursor {
+                    cursor = Some((column, rows.len() - 1));
+                }
+                rows.push(String::new());
+                column = 0;
+                continue;
+            }
+            let char_width = c.width().unwrap_or(0);
+            if column + char_width > width {
+                rows.push(String::new());
+                column = 0;
+            }
+            if idx == self.cursor {
+                cursor = Some((column, rows.len() - 1));
+            }
+            rows.last_mut().expect("at least one row").push(c);
+            column += char_width;
+        }
+        let (mut cursor_column, mut cursor_row) =
+            cursor.unwrap_or((column, rows.len() - 1));
+        if cursor_column >= width {
+            rows.push(String::new());
+            cursor_column = 0;
+            cursor_row += 1;
+        }
+
+        let height = height.max(1) as usize;
+        let scroll = (cursor_row + 1).saturating_sub(height);
+        let visible = rows.into_iter().skip(scroll).take(height).collect();
+        (visible, (cursor_column as u16, (cursor_row - scroll) as u16))
+    }
+}
+
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::new();
+        for c in text.chars() {
+            input.handle_key(&key(KeyCode::Char(c)));
+        }
+        input
+    }
+
+    #[test]
+    fn cursor_moves_over_multi_byte_characters() {
+        let mut input = typed("añ€😀");
+        assert_eq!(input.cursor, input.text().len());
+
+        input.handle_key(&key(KeyCode::Left));
+        assert_eq!(input.cursor, "añ€".len());
+        input.handle_key(&key(KeyCode::Backspace));
+        assert_eq!(input.text(), "añ😀");
+        input.handle_key(&key(KeyCode::Home));
+        input.handle_key(&key(KeyCode::Right));
+        input.handle_key(&key(KeyCode::Delete));
+        assert_eq!(input.text(), "a😀");
+        input.handle_key(&key(KeyCode::End));
+        assert_eq!(input.cursor, "a😀".len());
+        input.handle_key(&key(KeyCode::Right));
+        assert_eq!(input.cursor, "a😀".len());
+    }
+
+    #[test]
+    fn word_keys_and_line_keys() {
+        let mut input = typed("deep  work block");
+        input.handle_key(&ctrl('w'));
+        assert_eq!(input.text(), "deep  work ");
+        input.handle_key(&KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        assert_eq!(input.cursor, "deep  ".len());
+        input.handle_key(&KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL));
+        assert_eq!(input.cursor, "deep  work".len());
+        input.handle_key(&ctrl('a'));
+        assert_eq!(input.cursor, 0);
+        input.handle_key(&ctrl('e'));
+        input.handle_key(&ctrl('u'));
+        assert_eq!(input.text(), "");
+        assert!(!input.handle_key(&ctrl('x')));
+        assert!(!input.handle_key(&key(KeyCode::Esc)));
+    }
+
+    #[test]
+    fn ctrl_w_deletes_a_word_of_wide_characters() {
+        let mut input = typed("会議 メモ");
+        input.handle_key(&ctrl('w'));
+        assert_eq!(input.text(), "会議 ");
+        assert_eq!(input.cursor, "会議 ".len());
+    }
+
+    #[test]
+    fn paste_keeps_newlines_only_in_multiline_inputs() {
+        let mut single = typed("ab");
+        single.handle_key(&key(KeyCode::Left));
+        single.insert_str("x\r\ny\nz");
+        assert_eq!(single.text(), "ax y zb");
+        assert_eq!(single.cursor, "ax y z".len());
+
+        let mut multi = TextInput::multiline();
+        multi.insert_str("one\r\ntwo\rthree");
+        assert_eq!(multi.text(), "one\ntwo\nthree");
+
+        single.set("line\nbreak");
+        assert_eq!(single.text(), "line break");
+    }
+
+    #[test]
+    fn single_line_view_scrolls_by_display_width() {
+        let input = typed("日本語テキスト");
+        // Whole wide characters scroll out, leaving room for the cursor after the text
+        let (lines, cursor) = input.view(6, 1);
+        assert_eq!(lines, ["スト"]);
+        assert_eq!(cursor, (4, 0));
+        assert_eq!(display_width("日本語"), 6);
+
+        let input = typed("short");
+        assert_eq!(input.view(10, 1), (vec!["short".to_string()], (5, 0)));
+    }
+
+    #[test]
+    fn multiline_view_wraps_wide_characters_and_follows_the_cursor() {
+        let mut input = TextInput::multiline();
+        input.insert_str("日本語\nab");
+        let (lines, cursor) = input.view(4, 5);
+        assert_eq!(lines, ["日本", "語", "ab"]);
+        assert_eq!(cursor, (2, 2));
+
+        let (lines, cursor) = input.view(4, 2);
+        assert_eq!(lines, ["語", "ab"]);
+        assert_eq!(cursor, (2, 1));
+    }
+
+    #[test]
+    fn up_and_down_keep_the_display_column() {
+        let mut input = TextInput::multiline();
+        input.insert_str("日本語\nabcdef");
+        input.handle_key(&key(KeyCode::Left));
+        input.handle_key(&key(KeyCode::Left));
+        input.handle_key(&key(KeyCode::Up));
+        // Column 4 lands after the second wide character
+        assert_eq!(input.cursor, "日本".len());
+        input.handle_key(&key(KeyCode::Down));
+        assert_eq!(input.cursor, "日本語\nabcd".len());
+        input.handle_key(&key(KeyCode::Enter));
+        assert_eq!(input.text(), "日本語\nabcd\nef");
+    }
+}
@@ -0,0 +1,116 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::str::FromStr;
+
+use crate::config::ThemeConfig;
+
+/// Styles used by the TUI components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Group headers, summary bar, fast filter and the zen description
+    pub accent: Style,
+    /// Keybind bar, the active rating and the zen timer
+    pub highlight: Style,
+    /// Delete confirmation, query errors and "No active session"
+    pub error: Style,
+    /// Border of the input that currently has focus
+    pub active_border: Style,
+    /// Selected row of the session list
+    pub selection: Style,
+}
+
+pub const THEME_NAMES: [&str; 4] = ["dark", "light", "high-contrast", "no-color"];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            accent: Style::default().fg(Color::Cyan),
+            highlight: Style::default().fg(Color::Yellow),
+            error: Style::default().fg(Color::Red),
+            active_border: Style::default().fg(Color::Yellow),
+            selection: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            accent: Style::default().fg(Color::Blue),
+            highlight: Style::default().fg(Color::Magenta),
+            error: Style::default().fg(Color::Red),
+            active_border: Style::default().fg(Color::Blue),
+            selection: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            accent: Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            highlight: Style::default().fg(Color::Black).bg(Color::Yellow),
+            error: Style::default().fg(Color::White).bg(Color::Red),
+            active_border: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// Only modifiers, for terminals without colors and `NO_COLOR`
+    pub fn no_color() -> Theme {
+        Theme {
+            accent: Style::default(),
+            highlight: Style::default().add_modifier(Modifier::BOLD),
+            error: Style::default().add_modifier(Modifier::BOLD),
+            active_border: Style::default().add_modifier(Modifier::BOLD),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name.to_lowercase().as_str() {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "no-color" => Some(Theme::no_color()),
+            _ => None,
+        }
+    }
+
+    /// Picks the configured theme, `no-color` when `NO_COLOR` is set and no theme is named,
+    /// and applies the color overrides on top.
+    pub fn from_config(config: &ThemeConfig, no_color: bool) -> Result<Theme, String> {
+        let mut theme = match &config.name {
+            Some(name) => Theme::by_name(name).ok_or_else(|| {
+                format!("unknown theme '{}', expected one of {}", name, THEME_NAMES.join(", "))
+            })?,
+            None if no_color => Theme::no_color(),
+            None => Theme::dark(),
+        };
+
+        let overrides = [
+            (&config.accent, &mut theme.accent, "accent"),
+            (&config.highlight, &mut theme.highlight, "highlight"),
+            (&config.error, &mut theme.error, "error"),
+            (&config.active_border, &mut theme.active_border, "active_border"),
+        ];
+        for (color, style, field) in overrides {
+            if let Some(color) = color {
+                let color = Color::from_str(color)
+                    .map_err(|_| format!("theme '{}': unknown color '{}'", field, color))?;
+                *style = style.fg(color);
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Border style for an input block, highlighted while it has focus.
+    pub fn border(&self, active: bool) -> Style {
+        if active {
+            self.active_border
+        } else {
+            Style::default()
+        }
+    }
+}
@@ -52,3 +52,859 @@ fn test_cli_output() {
         stderr
     );
 }
+
+fn setup_session_dir(temp_dir: &tempfile::TempDir) -> (std::path::PathBuf, std::path::PathBuf) {
+    let session_dir = temp_dir.path().join("session");
+    fs::create_dir_all(&session_dir).expect("Failed to create pomodoro directory");
+
+    let config_content = format!(
+        r#"
+        [pomodoro_config]
+        pomodoro_session_dir = "{}/"
+    "#,
+        session_dir.display()
+    );
+
+    let config_path = temp_dir.path().join("pomodoro").join("config.toml");
+    fs::create_dir_all(config_path.parent().unwrap()).expect("Failed to create config directory");
+    fs::write(&config_path, config_content).expect("Failed to write config");
+    (session_dir, config_path)
+}
+
+fn run_cli(config_path: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_polpettone-pomodoro-timer"))
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+fn session_files(session_dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = fs::read_dir(session_dir)
+        .expect("Failed to read session dir")
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("yaml"))
+        .collect();
+    files.sort();
+    files
+}
+
+const LEGACY_SESSION: &str = r#"description: legacy session
+duration:
+  secs: 1500
+  nanos: 0
+start: 2025-05-31 14:05:26
+tags:
+- work
+"#;
+
+#[test]
+fn test_sessions_started_in_the_same_second_do_not_collide() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    for description in ["first", "second", "third"] {
+        let output = run_cli(&config_path, &["start", "-d", description]);
+        assert!(output.status.success());
+    }
+
+    assert_eq!(session_files(&session_dir).len(), 3);
+}
+
+#[test]
+fn test_legacy_session_files_are_read_without_rewriting() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let legacy_path = session_dir.join("20250531140526-session.yaml");
+    fs::write(&legacy_path, LEGACY_SESSION).unwrap();
+
+    let output = run_cli(&config_path, &["active"]);
+    assert!(output.status.success());
+    let output = run_cli(&config_path, &["find", "-d", "2025-05-31"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("legacy session"));
+
+    assert_eq!(session_files(&session_dir), vec![legacy_path.clone()]);
+    assert_eq!(fs::read_to_string(&legacy_path).unwrap(), LEGACY_SESSION);
+}
+
+#[test]
+fn test_migrate_dry_run_leaves_files_untouched() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let legacy_path = session_dir.join("20250531140526-session.yaml");
+    fs::write(&legacy_path, LEGACY_SESSION).unwrap();
+
+    let output = run_cli(&config_path, &["migrate", "--dry-run"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# version 0 -> 1"));
+    assert!(stdout.contains("-  secs: 1500"));
+    assert!(stdout.contains("+duration: 25m"));
+    assert!(stdout.contains("1 session files would be migrated"));
+
+    assert_eq!(fs::read_to_string(&legacy_path).unwrap(), LEGACY_SESSION);
+    assert!(!session_dir.join("backup").exists());
+}
+
+#[test]
+fn test_migrate_upgrades_files_and_keeps_a_backup() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    fs::write(session_dir.join("20250531140526-session.yaml"), LEGACY_SESSION).unwrap();
+
+    let output = run_cli(&config_path, &["migrate"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Migrated 1 session files"));
+
+    let files = session_files(&session_dir);
+    assert_eq!(files.len(), 1);
+    let content = fs::read_to_string(&files[0]).unwrap();
+    assert!(content.starts_with("version: 1\n"));
+    assert!(content.contains("duration: 25m\n"));
+    assert!(content.contains("- work\n"));
+
+    let backup_dirs: Vec<_> = fs::read_dir(session_dir.join("backup")).unwrap().collect();
+    assert_eq!(backup_dirs.len(), 1);
+    let backup = backup_dirs[0]
+        .as_ref()
+        .unwrap()
+        .path()
+        .join("20250531140526-session.yaml");
+    assert_eq!(fs::read_to_string(backup).unwrap(), LEGACY_SESSION);
+
+    let output = run_cli(&config_path, &["migrate"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("All session files are up to date"));
+}
+
+#[test]
+fn test_sessions_from_a_newer_version_are_refused() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let content = "version: 99\nid: NEWER\ndescription: from the future\nduration: 25m\n\
+                   start: 2025-05-31 14:00:00\ntags:\n- work\nfuture_field: keep me\n";
+    let path = session_dir.join("NEWER-session.yaml");
+    fs::write(&path, content).unwrap();
+
+    for args in [&["find", "-d", "2025-05-31"][..], &["tags", "rename", "work", "job"][..]] {
+        let output = run_cli(&config_path, args);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("has version 99"));
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn test_hand_edited_durations_are_read() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    for (i, duration) in ["1h30m", "1h 5m 30s", "45", "2H"].iter().enumerate() {
+        let content = format!(
+            "version: 1\nid: session{}\ndescription: edited {}\nduration: {}\nstart: 2025-05-31 1{}:00:00\n",
+            i, i, duration, i
+        );
+        fs::write(session_dir.join(format!("session{}-session.yaml", i)), content).unwrap();
+    }
+
+    let output = run_cli(
+        &config_path,
+        &[
+            "find-sessions-in-range",
+            "2025-05-31 00:00:00",
+            "2025-05-31 23:59:59",
+            "-e",
+        ],
+    );
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| 90:00     | edited 0"));
+    assert!(stdout.contains("| 65:30     | edited 1"));
+    assert!(stdout.contains("| 45:00     | edited 2"));
+    assert!(stdout.contains("| 120:00    | edited 3"));
+}
+
+#[test]
+fn test_huge_durations_are_rejected_without_panicking() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    let output = run_cli(&config_path, &["start", "-t", "18446744073709551615"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too long"));
+    assert!(!stderr.contains("panicked"));
+    assert!(session_files(&session_dir).is_empty());
+
+    write_session(&session_dir, "HUGE", "description: huge\nduration: 18446744073709551615\n");
+    let output = run_cli(&config_path, &["find", "-d", "2025-05-31"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too long"));
+    assert!(!stderr.contains("panicked"));
+    fs::remove_file(session_dir.join("HUGE-session.yaml")).unwrap();
+
+    // These fit into a duration but end after the last date chrono can represent
+    let output = run_cli(&config_path, &["start", "-t", "200000000000", "-d", "forever"]);
+    assert!(output.status.success());
+    write_session(
+        &session_dir,
+        "EDITED",
+        "description: edited\nduration: 100000000000h\nstate: Running\n",
+    );
+    let output = run_cli(&config_path, &["active"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("forever"));
+    assert!(stdout.contains("edited"));
+    for _ in 0..2 {
+        let output = run_cli(&config_path, &["stop"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+    let output = run_cli(&config_path, &["stop"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No running session"));
+}
+
+#[test]
+fn test_concurrent_writers_leave_only_complete_session_files() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    let children: Vec<_> = (0..8)
+        .map(|i| {
+            Command::new(env!("CARGO_BIN_EXE_polpettone-pomodoro-timer"))
+                .arg("--config")
+                .arg(&config_path)
+                .args(["start", "-d", &format!("parallel {}", i)])
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .expect("Failed to execute command")
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let output = run_cli(&config_path, &["active"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for i in 0..8 {
+        assert!(stdout.contains(&format!("parallel {}", i)));
+    }
+
+    let leftovers: Vec<_> = fs::read_dir(&session_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "temp files left behind: {:?}", leftovers);
+}
+
+fn write_deleted_session(session_dir: &std::path::Path, id: &str, deleted_at: &str) {
+    let content = format!(
+        "version: 1\nid: {}\ndescription: billable {}\nduration: 25m\nstart: 2025-05-31 14:00:00\nstate: Deleted\ndeleted_at: {}\nstate_before_delete: Done\n",
+        id, id, deleted_at
+    );
+    fs::write(session_dir.join(format!("{}-session.yaml", id)), content).unwrap();
+}
+
+#[test]
+fn test_trash_list_and_restore() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_deleted_session(&session_dir, "AAA111", "2025-06-01 10:00:00");
+    write_deleted_session(&session_dir, "BBB222", "2025-06-02 10:00:00");
+
+    let output = run_cli(&config_path, &["trash", "list"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("AAA111"));
+    assert!(stdout.contains("BBB222"));
+
+    let output = run_cli(&config_path, &["trash", "restore", "AAA"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Restored"));
+
+    let content = fs::read_to_string(session_dir.join("AAA111-session.yaml")).unwrap();
+    assert!(content.contains("state: Done"));
+    assert!(content.contains("deleted_at: null"));
+
+    let output = run_cli(&config_path, &["trash", "list"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("AAA111"));
+
+    let output = run_cli(&config_path, &["trash", "restore", "ZZZ"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_trash_purge_older_than() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_deleted_session(&session_dir, "OLD111", "2020-01-01 10:00:00");
+    let recent = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    write_deleted_session(&session_dir, "NEW222", &recent);
+
+    let output = run_cli(&config_path, &["trash", "purge", "--older-than", "30", "-y"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Purged 1 sessions"));
+    assert!(!session_dir.join("OLD111-session.yaml").exists());
+    assert!(session_dir.join("NEW222-session.yaml").exists());
+
+    for days in ["-1", "9999999999999", "4294967295"] {
+        let output = run_cli(&config_path, &["trash", "purge", "--older-than", days, "-y"]);
+        assert!(!output.status.success());
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+        assert!(session_dir.join("NEW222-session.yaml").exists());
+    }
+
+    let output = run_cli(&config_path, &["trash", "purge", "-y"]);
+    assert!(output.status.success());
+    assert!(session_files(&session_dir).is_empty());
+}
+
+fn write_session(session_dir: &std::path::Path, id: &str, body: &str) {
+    let content = format!("version: 1\nid: {}\nstart: 2025-05-31 14:00:00\n{}", id, body);
+    fs::write(session_dir.join(format!("{}-session.yaml", id)), content).unwrap();
+}
+
+#[test]
+fn test_find_sessions_with_query() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_session(
+        &session_dir,
+        "S1",
+        "description: rust hacking\nduration: 50m\ntags: [rust]\nnotes: retro went well\nratings:\n  mental_energy: 3\n  physical_energy: 3\n  cognitive_load: 3\n  motivation: 5\n",
+    );
+    write_session(
+        &session_dir,
+        "S2",
+        "description: rust standup\nduration: 15m\ntags: [rust, meeting]\n",
+    );
+    write_session(
+        &session_dir,
+        "S3",
+        "description: reading\nduration: 25m\ntags: [learning]\nstate: Canceled\n",
+    );
+
+    let find = |query: &str| {
+        let output = run_cli(
+            &config_path,
+            &[
+                "find-sessions-in-range",
+                "2025-05-31 00:00:00",
+                "2025-05-31 23:59:59",
+                "-e",
+                "-q",
+                query,
+            ],
+        );
+        assert!(output.status.success(), "query {} failed", query);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = find("tag:rust -tag:meeting");
+    assert!(stdout.contains("rust hacking"));
+    assert!(!stdout.contains("rust standup"));
+
+    let stdout = find("rating.motivation>=4 duration>30m note:\"retro\"");
+    assert!(stdout.contains("rust hacking"));
+    assert!(!stdout.contains("reading"));
+
+    let stdout = find("state:canceled OR tag:meeting");
+    assert!(stdout.contains("reading"));
+    assert!(stdout.contains("rust standup"));
+    assert!(!stdout.contains("rust hacking"));
+
+    let stdout = find("(tag:learning OR tag:meeting) AND duration<20m");
+    assert!(stdout.contains("rust standup"));
+    assert!(!stdout.contains("reading"));
+
+    let output = run_cli(
+        &config_path,
+        &["find-session-from-today", "-q", "rating.mood>3"],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_find_with_saved_view() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let mut config = fs::read_to_string(&config_path).unwrap();
+    config.push_str(
+        r#"
+[[views]]
+name = "May, client X"
+date = "2025-05-01 - 2025-05-31"
+search = "tag:clientx"
+"#,
+    );
+    fs::write(&config_path, config).unwrap();
+
+    write_session(&session_dir, "S1", "description: acme backend\nduration: 25m\ntags: [clientx]\n");
+    write_session(&session_dir, "S2", "description: internal\nduration: 25m\ntags: [internal]\n");
+    write_session(
+        &session_dir,
+        "S3",
+        "description: deleted acme\nduration: 25m\ntags: [clientx]\nstate: Deleted\n",
+    );
+
+    let output = run_cli(&config_path, &["find", "--view", "may, client x"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme backend"));
+    assert!(!stdout.contains("internal"));
+    assert!(!stdout.contains("deleted acme"));
+
+    let output = run_cli(
+        &config_path,
+        &["find", "--view", "May, client X", "-d", "2025-06-01"],
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("acme backend"));
+
+    let output = run_cli(&config_path, &["find", "--view", "unknown"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_tui_rejects_invalid_keymap() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (_session_dir, config_path) = setup_session_dir(&temp_dir);
+    let base_config = fs::read_to_string(&config_path).unwrap();
+
+    fs::write(&config_path, format!("{}\n[keymap]\nfly = \"f\"\n", base_config)).unwrap();
+    let output = run_cli(&config_path, &["tui"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown keymap action 'fly'"));
+
+    // Moving `down` to `n` without moving `notes` leaves two actions on one key
+    fs::write(&config_path, format!("{}\n[keymap]\ndown = \"n\"\n", base_config)).unwrap();
+    let output = run_cli(&config_path, &["tui"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("bound to both"));
+
+    // The rating form moves with `down` and rates with `increase`, one key can't do both
+    fs::write(&config_path, format!("{}\n[keymap]\ndown = \"l\"\n", base_config)).unwrap();
+    let output = run_cli(&config_path, &["tui"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("key 'l' is bound to both 'down' and 'increase'"));
+}
+
+#[test]
+fn test_tui_rejects_invalid_theme() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (_session_dir, config_path) = setup_session_dir(&temp_dir);
+    let base_config = fs::read_to_string(&config_path).unwrap();
+
+    fs::write(&config_path, format!("{}\n[theme]\nname = \"solarized\"\n", base_config)).unwrap();
+    let output = run_cli(&config_path, &["tui"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown theme 'solarized'"));
+
+    fs::write(
+        &config_path,
+        format!("{}\n[theme]\nname = \"light\"\nhighlight = \"mauve\"\n", base_config),
+    )
+    .unwrap();
+    let output = run_cli(&config_path, &["tui"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown color 'mauve'"));
+}
+
+#[test]
+fn test_tags_list_rename_merge_delete() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_session(&session_dir, "S1", "description: a\nduration: 25m\ntags: [Rust, focus]\n");
+    write_session(&session_dir, "S2", "description: b\nduration: 25m\ntags: [rust-lang, rust]\n");
+    write_session(&session_dir, "S3", "description: c\nduration: 25m\ntags: [meeting]\n");
+
+    let output = run_cli(&config_path, &["tags", "list"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("rust-lang"));
+    assert!(stdout.contains("meeting"));
+
+    let output = run_cli(&config_path, &["tags", "merge", "Rust", "rust-lang", "--into", "rust"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated 2 sessions"));
+    let s1 = fs::read_to_string(session_dir.join("S1-session.yaml")).unwrap();
+    assert!(s1.contains("- rust\n- focus"));
+    // The merged tag isn't listed twice
+    let s2 = fs::read_to_string(session_dir.join("S2-session.yaml")).unwrap();
+    assert_eq!(s2.matches("rust").count(), 1);
+
+    let output = run_cli(&config_path, &["tags", "rename", "meeting", "meetings"]);
+    assert!(output.status.success());
+    let s3 = fs::read_to_string(session_dir.join("S3-session.yaml")).unwrap();
+    assert!(s3.contains("- meetings"));
+
+    let output = run_cli(&config_path, &["tags", "delete", "focus", "-y"]);
+    assert!(output.status.success());
+    let s1 = fs::read_to_string(session_dir.join("S1-session.yaml")).unwrap();
+    assert!(!s1.contains("focus"));
+    assert!(s1.contains("- rust"));
+}
+
+#[test]
+fn test_tags_commands_rewrite_legacy_files_in_place() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let first = session_dir.join("20250531140526-session.yaml");
+    let second = session_dir.join("20250531150000-session.yaml");
+    fs::write(&first, LEGACY_SESSION.replace("- work", "- rust\n- focus")).unwrap();
+    fs::write(&second, LEGACY_SESSION.replace("- work", "- rust\n- review")).unwrap();
+
+    let output = run_cli(&config_path, &["tags", "rename", "rust", "Rust"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated 2 sessions"));
+    let output = run_cli(&config_path, &["tags", "merge", "focus", "review", "--into", "deep"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated 2 sessions"));
+    let output = run_cli(&config_path, &["tags", "delete", "deep", "-y"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated 2 sessions"));
+
+    assert_eq!(session_files(&session_dir), vec![first.clone(), second.clone()]);
+    for path in [first, second] {
+        let content = fs::read_to_string(path).unwrap();
+        assert!(content.contains("tags:\n- Rust\n"), "{}", content);
+        assert!(!content.contains("deep"), "{}", content);
+    }
+}
+
+#[test]
+fn test_hierarchical_tags_filter_and_roll_up() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_session(
+        &session_dir,
+        "S1",
+        "description: api work\nduration: 50m\ntags: [client/acme/backend, client/acme/frontend]\n",
+    );
+    write_session(&session_dir, "S2", "description: ui work\nduration: 25m\ntags: [client/acme/frontend]\n");
+    write_session(&session_dir, "S3", "description: other\nduration: 10m\ntags: [client/acmecorp]\n");
+
+    let output = run_cli(&config_path, &["find", "-q", "tag:client/acme", "-e"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("api work"));
+    assert!(stdout.contains("ui work"));
+    assert!(!stdout.contains("other"));
+    // S1 counts once for acme even though it has two acme tags
+    assert!(stdout.contains("|   acme                       |        2 | 01:15     |"));
+    assert!(stdout.contains("|     backend                  |        1 | 00:50     |"));
+
+    let output = run_cli(&config_path, &["tags", "rename", "client/acme", "customer/acme"]);
+    assert!(output.status.success());
+    let s1 = fs::read_to_string(session_dir.join("S1-session.yaml")).unwrap();
+    assert!(s1.contains("- customer/acme/backend\n- customer/acme/frontend"));
+    let s3 = fs::read_to_string(session_dir.join("S3-session.yaml")).unwrap();
+    assert!(s3.contains("tags: [client/acmecorp]"));
+}
+
+#[test]
+fn test_start_with_preset() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let base_config = fs::read_to_string(&config_path).unwrap();
+    let presets = r#"
+[[presets]]
+name = "deep-work"
+description = "deep work"
+duration = "50m"
+tags = ["focus", "client/acme"]
+project = "acme"
+breaks = ["10m", "30m"]
+"#;
+    fs::write(&config_path, format!("{}{}", base_config, presets)).unwrap();
+
+    let output = run_cli(&config_path, &["start", "--preset", "Deep-Work"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Break afterwards: 10m"));
+    let files = session_files(&session_dir);
+    assert_eq!(files.len(), 1);
+    let session = fs::read_to_string(&files[0]).unwrap();
+    assert!(session.contains("description: deep work"));
+    assert!(session.contains("duration: 50m"));
+    assert!(session.contains("- focus\n- client/acme"));
+    assert!(session.contains("project: acme"));
+
+    // Options given on the command line win over the preset
+    let output = run_cli(&config_path, &["start", "-p", "deep-work", "-t", "15"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Break afterwards: 30m"));
+    let durations: Vec<_> = session_files(&session_dir)
+        .iter()
+        .map(|f| fs::read_to_string(f).unwrap())
+        .filter(|s| s.contains("duration: 15m"))
+        .collect();
+    assert_eq!(durations.len(), 1);
+
+    let output = run_cli(&config_path, &["start", "--preset", "shallow-work"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no preset named shallow-work"));
+}
+
+#[test]
+fn test_schedule_and_start_due_sessions() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    // 2099-01-03 is a Saturday, so the weekday schedule begins on Monday
+    let output = run_cli(
+        &config_path,
+        &[
+            "schedule", "standup prep", "--at", "2099-01-03 09:45", "--repeat", "weekdays", "-t",
+            "15",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("weekdays from 2099-01-05 09:45"));
+    let planned = fs::read_to_string(&session_files(&session_dir)[0]).unwrap();
+    assert!(planned.contains("state: Planned"));
+    assert!(planned.contains("repeat: weekdays"));
+    assert!(planned.contains("start: 2099-01-05 09:45:00"));
+
+    let now = chrono::Utc::now();
+    let minute_ago = (now - chrono::Duration::minutes(1)).format("%Y-%m-%d %H:%M:%S");
+    let hours_ago = (now - chrono::Duration::hours(2)).format("%Y-%m-%d %H:%M:%S");
+    let write_planned = |id: &str, start: &str, extra: &str| {
+        let content = format!(
+            "version: 1\nid: {}\ndescription: {}\nduration: 25m\nstart: {}\nstate: Planned\n{}",
+            id, id, start, extra
+        );
+        fs::write(session_dir.join(format!("{}-session.yaml", id)), content).unwrap();
+    };
+    write_planned("ONEOFF", &minute_ago.to_string(), "");
+    write_planned("DAILY", &minute_ago.to_string(), "repeat: daily\n");
+    write_planned("MISSED", &hours_ago.to_string(), "");
+
+    let mut watch = Command::new(env!("CARGO_BIN_EXE_polpettone-pomodoro-timer"))
+        .arg("--config")
+        .arg(&config_path)
+        .arg("watch")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("Failed to execute command");
+    let read = |id: &str| fs::read_to_string(session_dir.join(format!("{}-session.yaml", id)));
+    for _ in 0..50 {
+        if read("MISSED").unwrap().contains("Canceled") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    watch.kill().unwrap();
+    watch.wait().unwrap();
+
+    assert!(read("ONEOFF").unwrap().contains("state: Running"));
+    assert!(read("MISSED").unwrap().contains("state: Canceled"));
+    // The repeating session starts a copy and moves on to tomorrow
+    let daily = read("DAILY").unwrap();
+    assert!(daily.contains("state: Planned"));
+    let tomorrow =
+        (now - chrono::Duration::minutes(1) + chrono::Duration::days(1)).format("%Y-%m-%d");
+    assert!(daily.contains(&format!("start: {}", tomorrow)));
+    let started: Vec<_> = session_files(&session_dir)
+        .iter()
+        .map(|f| fs::read_to_string(f).unwrap())
+        .filter(|s| s.contains("description: DAILY") && s.contains("state: Running"))
+        .collect();
+    assert_eq!(started.len(), 1);
+    assert!(!started[0].contains("repeat"));
+
+    // A bare time that has passed moves to tomorrow, a date in the past is a mistake
+    let output = run_cli(&config_path, &["schedule", "retro", "--at", "2025-06-02 09:45"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2025-06-02 09:45 is in the past"));
+    let output = run_cli(&config_path, &["schedule", "retro", "--at", "00:00"]);
+    assert!(output.status.success());
+    let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1)).format("%Y-%m-%d");
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&format!("for {} 00:00", tomorrow)));
+}
+
+#[test]
+fn test_overtime_sessions_report_the_actual_time() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+
+    let output = run_cli(&config_path, &["start", "-d", "open ended", "--stopwatch"]);
+    assert!(output.status.success());
+    let stopwatch = fs::read_to_string(&session_files(&session_dir)[0]).unwrap();
+    assert!(stopwatch.contains("duration: 0s"));
+    assert!(stopwatch.contains("overtime: true"));
+
+    // Overtime sessions past their duration still count as active
+    let started = chrono::Utc::now() - chrono::Duration::minutes(40);
+    let content = format!(
+        "version: 1\nid: OVER\ndescription: ran long\nduration: 25m\nstart: {}\nstate: Running\n",
+        started.format("%Y-%m-%d %H:%M:%S")
+    );
+    fs::write(session_dir.join("OVER-session.yaml"), content + "overtime: true\n").unwrap();
+    let output = run_cli(&config_path, &["active"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("ran long"));
+
+    // A finished session counts until it ended, not its planned duration
+    write_session(
+        &session_dir,
+        "S1",
+        "description: finished late\nduration: 25m\nstate: Done\nended_at: 2025-05-31 14:40:00\n",
+    );
+    let output = run_cli(
+        &config_path,
+        &["find-sessions-in-range", "2025-05-31 00:00:00", "2025-05-31 23:59:59", "-e"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| 40:00     | finished late      |"));
+    assert!(stdout.contains("| Total  |            --         | 00:40     |"));
+}
+
+#[test]
+fn test_canceled_sessions_count_until_they_ended() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let write_ended = |id: &str, description: &str, start: &str, state: &str, end: &str| {
+        let content = format!(
+            "version: 1\nid: {}\ndescription: {}\nduration: 25m\nstart: 2025-05-31 {}\n\
+             state: {}\nended_at: 2025-05-31 {}\n",
+            id, description, start, state, end
+        );
+        fs::write(session_dir.join(format!("{}-session.yaml", id)), content).unwrap();
+    };
+    write_ended("S1", "full run", "14:00:00", "Done", "14:25:00");
+    write_ended("S2", "gave up", "15:00:00", "Canceled", "15:03:00");
+    // Would have run into the range, but was interrupted before it
+    write_ended("S3", "early exit", "13:50:00", "Canceled", "13:53:00");
+
+    let output = run_cli(
+        &config_path,
+        &["find-sessions-in-range", "2025-05-31 14:00:00", "2025-05-31 23:59:59", "-e"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| 03:00     | gave up            |"));
+    assert!(!stdout.contains("early exit"));
+    assert!(stdout.contains("| Total  |            --         | 00:28     |"));
+}
+
+#[test]
+fn test_ended_sessions_are_not_active() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let started = (chrono::Utc::now() - chrono::Duration::minutes(5)).format("%Y-%m-%d %H:%M:%S");
+    for (id, state) in [("RUNNING", "Running"), ("CANCELED", "Canceled"), ("STOPPED", "Done")] {
+        let content = format!(
+            "version: 1\nid: {}\ndescription: {} session\nduration: 25m\nstart: {}\nstate: {}\n",
+            id,
+            id.to_lowercase(),
+            started,
+            state
+        );
+        fs::write(session_dir.join(format!("{}-session.yaml", id)), content).unwrap();
+    }
+
+    let output = run_cli(&config_path, &["active"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("running session"));
+    assert!(!stdout.contains("canceled session"));
+    assert!(!stdout.contains("stopped session"));
+}
+
+#[test]
+fn test_stop_trims_the_running_session() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let started = chrono::Utc::now() - chrono::Duration::seconds(10 * 60 + 20);
+    let content = format!(
+        "version: 1\nid: RUN\ndescription: almost done\nduration: 25m\nstart: {}\nstate: Running\n",
+        started.format("%Y-%m-%d %H:%M:%S")
+    );
+    fs::write(session_dir.join("RUN-session.yaml"), content).unwrap();
+
+    let output = run_cli(&config_path, &["stop"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Stopped almost done after 10m"));
+    let stopped = fs::read_to_string(session_dir.join("RUN-session.yaml")).unwrap();
+    assert!(stopped.contains("state: Done"));
+    assert!(stopped.contains("end_reason: StoppedEarly"));
+    assert!(stopped.contains("duration: 10m"));
+
+    let output = run_cli(&config_path, &["stop"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No running session"));
+}
+
+#[test]
+fn test_stop_a_running_legacy_session() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    let started = chrono::Utc::now() - chrono::Duration::minutes(5);
+    let legacy_path = session_dir.join("20250531140526-session.yaml");
+    let content = LEGACY_SESSION.replace(
+        "start: 2025-05-31 14:05:26",
+        &format!("start: {}\nstate: Running", started.format("%Y-%m-%d %H:%M:%S")),
+    );
+    fs::write(&legacy_path, content).unwrap();
+
+    let output = run_cli(&config_path, &["stop"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Stopped legacy session after 5m"));
+    assert_eq!(session_files(&session_dir), vec![legacy_path.clone()]);
+    let stopped = fs::read_to_string(&legacy_path).unwrap();
+    assert!(stopped.contains("state: Done"));
+    assert!(stopped.contains("end_reason: StoppedEarly"));
+}
+
+#[test]
+fn test_log_a_past_session() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let (session_dir, config_path) = setup_session_dir(&temp_dir);
+    write_session(&session_dir, "EXISTING", "description: planning\nduration: 25m\nstate: Done\n");
+
+    let output = run_cli(
+        &config_path,
+        &[
+            "log",
+            "--at",
+            "2025-05-31 14:10",
+            "--duration",
+            "50m",
+            "-d",
+            "forgot the timer",
+            "--tags",
+            "rust, review",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Logged forgot the timer from 2025-05-31 14:10 to 15:00"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("overlaps with planning"));
+
+    let logged: Vec<String> = session_files(&session_dir)
+        .iter()
+        .map(|p| fs::read_to_string(p).unwrap())
+        .filter(|c| c.contains("forgot the timer"))
+        .collect();
+    assert_eq!(logged.len(), 1);
+    assert!(logged[0].contains("state: Done"));
+    assert!(logged[0].contains("duration: 50m"));
+    assert!(logged[0].contains("- rust\n- review"));
+    assert!(logged[0].contains("end_reason: Completed"));
+
+    let future = (chrono::Utc::now() + chrono::Duration::hours(1)).format("%Y-%m-%d %H:%M");
+    let output = run_cli(&config_path, &["log", "--at", &future.to_string()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has to be over"));
+    let output = run_cli(&config_path, &["log", "--at", "2025-05-31 18:00", "-t", "0"]);
+    assert!(!output.status.success());
+    let output = run_cli(&config_path, &["log", "--at", "2025-05-31 10:00", "-t", "100000000000h"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("can't be that long"));
+    assert!(!stderr.contains("panicked"));
+    assert_eq!(session_files(&session_dir).len(), 2);
+}